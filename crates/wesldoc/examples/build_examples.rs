@@ -6,6 +6,7 @@ fn main() -> anyhow::Result<()> {
     for package in ["primitives", "math_utils", "pbr"] {
         Args::parse_from([
             "wesldoc",
+            "doc",
             &format!("./example_packages/{package}"),
             "--no-deps",
             "--statistics",
@@ -1,23 +1,65 @@
-use anyhow::{Context, Result};
-use cargo_metadata::{DependencyKind, Package, PackageId};
+use anyhow::{Context, Result, bail};
+use cargo_metadata::{DependencyKind, Metadata, Package, PackageId};
 use std::{
     collections::{HashMap, HashSet},
-    path::PathBuf,
+    fs,
+    path::{Path, PathBuf},
 };
 use wesldoc_ast::Version;
 
 pub struct CargoMetadata {
     packages: HashMap<PackageId, CargoPackage>,
-    root_id: PackageId,
+    /// `None` for virtual workspace manifests.
+    root_id: Option<PackageId>,
+    workspace_members: Vec<PackageId>,
+}
+
+/// Which packages of a workspace to document, see [`CargoMetadata::select`].
+#[derive(Debug, Clone, Default)]
+pub struct PackageSelection {
+    pub workspace: bool,
+    pub packages: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+/// How `cargo metadata` is run, see [`CargoMetadata::resolve`].
+#[derive(Debug, Clone, Default)]
+pub struct MetadataOptions {
+    pub manifest_path: PathBuf,
+    pub offline: bool,
+    pub frozen: bool,
+    pub locked: bool,
 }
 
 impl CargoMetadata {
-    pub fn resolve(base_path: impl Into<PathBuf>) -> Result<Self> {
-        let base_path = base_path.into();
-        let metadata = cargo_metadata::MetadataCommand::new()
-            .manifest_path(base_path.join("Cargo.toml"))
-            .exec()?;
+    pub fn resolve(options: &MetadataOptions) -> Result<Self> {
+        let mut command = cargo_metadata::MetadataCommand::new();
+        command.manifest_path(&options.manifest_path);
+        let flags = [
+            (options.offline, "--offline"),
+            (options.frozen, "--frozen"),
+            (options.locked, "--locked"),
+        ];
+        command.other_options(
+            flags
+                .into_iter()
+                .filter(|(enabled, _)| *enabled)
+                .map(|(_, flag)| flag.to_string())
+                .collect::<Vec<_>>(),
+        );
+        Self::from_metadata(command.exec()?)
+    }
+
+    /// Reads a pre-generated `cargo metadata --format-version 1` dump instead of running cargo.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read cargo metadata from {}", path.display()))?;
+        let metadata = cargo_metadata::MetadataCommand::parse(json)
+            .with_context(|| format!("invalid cargo metadata in {}", path.display()))?;
+        Self::from_metadata(metadata)
+    }
 
+    fn from_metadata(metadata: Metadata) -> Result<Self> {
         let mut packages = metadata
             .packages
             .iter()
@@ -46,32 +88,79 @@ impl CargoMetadata {
             }
         }
 
-        let root_id = metadata
-            .root_package()
-            .context("no root package")?
-            .id
-            .clone();
+        let root_id = metadata.root_package().map(|package| package.id.clone());
+        let workspace_members = metadata.workspace_members.clone();
 
-        Ok(Self { packages, root_id })
+        Ok(Self {
+            packages,
+            root_id,
+            workspace_members,
+        })
     }
 
     pub fn package(&self, package_id: &PackageId) -> Option<&CargoPackage> {
         self.packages.get(package_id)
     }
 
-    pub fn iter_packages(&self, max_depth: usize) -> impl Iterator<Item = &CargoPackage> {
-        self.packages
-            .get(&self.root_id)
+    /// Whether the manifest is a virtual workspace manifest without a package of its own.
+    pub fn is_virtual(&self) -> bool {
+        self.root_id.is_none()
+    }
+
+    /// Selects the packages to start documenting from. Without `--workspace` or `--package`
+    /// this is the root package, or all workspace members for a virtual manifest.
+    pub fn select(&self, selection: &PackageSelection) -> Result<Vec<&CargoPackage>> {
+        let roots = if !selection.packages.is_empty() {
+            selection
+                .packages
+                .iter()
+                .map(|name| {
+                    self.workspace_members
+                        .iter()
+                        .filter_map(|id| self.packages.get(id))
+                        .chain(self.packages.values())
+                        .find(|package| package.name() == *name)
+                        .with_context(|| format!("package '{name}' not found"))
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else if selection.workspace || self.root_id.is_none() {
+            self.workspace_members
+                .iter()
+                .filter_map(|id| self.packages.get(id))
+                .collect()
+        } else {
+            self.root_id
+                .iter()
+                .filter_map(|id| self.packages.get(id))
+                .collect()
+        };
+
+        for name in &selection.exclude {
+            if !roots.iter().any(|package| package.name() == *name) {
+                bail!("excluded package '{name}' is not a selected workspace member");
+            }
+        }
+
+        Ok(roots
             .into_iter()
-            .flat_map(move |package| IterPackages {
-                metadata: self,
-                max_depth,
-                stack: vec![IterPackagesCurrent {
-                    package,
-                    deps: package.deps.values(),
-                }],
-                visited: HashSet::new(),
-            })
+            .filter(|package| !selection.exclude.contains(&package.name()))
+            .collect())
+    }
+
+    /// Iterates over the root packages and their dependencies up to `max_depth`. Packages shared
+    /// by several roots are only returned once.
+    pub fn iter_packages<'a>(
+        &'a self,
+        roots: &[&'a CargoPackage],
+        max_depth: usize,
+    ) -> impl Iterator<Item = &'a CargoPackage> {
+        IterPackages {
+            metadata: self,
+            max_depth,
+            roots: roots.to_vec().into_iter(),
+            stack: Vec::new(),
+            visited: HashSet::new(),
+        }
     }
 }
 
@@ -79,6 +168,7 @@ struct IterPackages<'a> {
     metadata: &'a CargoMetadata,
     max_depth: usize,
 
+    roots: std::vec::IntoIter<&'a CargoPackage>,
     stack: Vec<IterPackagesCurrent<'a>>,
     visited: HashSet<&'a PackageId>,
 }
@@ -88,6 +178,18 @@ impl<'a> Iterator for IterPackages<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
+            // Start with the next root once all packages of the previous one are returned
+            if self.stack.is_empty() {
+                let root = self.roots.next()?;
+                if !self.visited.insert(&root.package.id) {
+                    continue;
+                }
+                self.stack.push(IterPackagesCurrent {
+                    package: root,
+                    deps: root.deps.values(),
+                });
+            }
+
             if self.stack.len() <= self.max_depth
                 && let Some(dep_id) = self.stack.last_mut()?.deps.next()
             {
@@ -141,4 +243,23 @@ impl CargoPackage {
     pub fn dep(&self, name: &str) -> Option<&PackageId> {
         self.deps.get(name)
     }
+
+    /// Root URLs of externally hosted dependency docs from
+    /// `[package.metadata.wesldoc.extern-html-root-urls]`, keyed by package name.
+    pub fn extern_html_root_urls(&self) -> Result<HashMap<String, String>> {
+        match self
+            .package
+            .metadata
+            .get("wesldoc")
+            .and_then(|wesldoc| wesldoc.get("extern-html-root-urls"))
+        {
+            Some(urls) => serde_json::from_value(urls.clone()).with_context(|| {
+                format!(
+                    "invalid [package.metadata.wesldoc.extern-html-root-urls] in package '{}'",
+                    self.name()
+                )
+            }),
+            None => Ok(HashMap::new()),
+        }
+    }
 }
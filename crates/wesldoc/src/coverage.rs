@@ -0,0 +1,137 @@
+use serde_json::{Value, json};
+use std::fmt::Write as _;
+use wesldoc_ast::{Coverage, CoverageCount, Version};
+
+/// Coverage of all documented packages, written as JSON at the end of the run.
+#[derive(Default)]
+pub struct CoverageReport {
+    packages: Vec<Value>,
+}
+
+impl CoverageReport {
+    pub fn add(&mut self, package: &str, version: &Version, coverage: &Coverage) {
+        self.packages.push(json!({
+            "package": package,
+            "version": version.to_string(),
+            "total": count_json(coverage.total()),
+            "examples": count_json(coverage.examples()),
+            "kinds": coverage
+                .kinds()
+                .into_iter()
+                .map(|(kind, count)| (kind.name().to_string(), count_json(count)))
+                .collect::<serde_json::Map<_, _>>(),
+            "modules": coverage
+                .modules
+                .iter()
+                .map(|module| json!({
+                    "module": module.path.join("::"),
+                    "total": count_json(module.total()),
+                    "examples": count_json(module.examples),
+                    "kinds": module
+                        .kinds
+                        .iter()
+                        .map(|(kind, count)| (kind.name().to_string(), count_json(*count)))
+                        .collect::<serde_json::Map<_, _>>(),
+                }))
+                .collect::<Vec<_>>(),
+        }));
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "packages": self.packages })
+    }
+}
+
+/// Formats the coverage as tables per module and per kind, similar to `rustdoc --show-coverage`.
+/// Examples are counted for modules and items, not for struct members and parameters.
+pub fn coverage_table(coverage: &Coverage) -> String {
+    let modules = coverage
+        .modules
+        .iter()
+        .filter(|module| module.total().total > 0)
+        .map(|module| (module.path.join("::"), module.total(), module.examples));
+    let kinds = coverage
+        .kinds()
+        .into_iter()
+        .map(|(kind, count)| (kind.to_string(), count, CoverageCount::default()));
+
+    let mut out = String::new();
+    table(
+        &mut out,
+        "Module",
+        modules,
+        (coverage.total(), coverage.examples()),
+    );
+    out.push('\n');
+    table(
+        &mut out,
+        "Kind",
+        kinds,
+        (coverage.total(), coverage.examples()),
+    );
+    out
+}
+
+fn table(
+    out: &mut String,
+    title: &str,
+    rows: impl Iterator<Item = (String, CoverageCount, CoverageCount)>,
+    total: (CoverageCount, CoverageCount),
+) {
+    let rows = rows.collect::<Vec<_>>();
+    let width = rows
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .chain([title.len(), "Total".len()])
+        .max()
+        .unwrap_or_default();
+    let separator = format!(
+        "+-{}-+------------+------------+------------+------------+",
+        "-".repeat(width)
+    );
+
+    let _ = writeln!(out, "{separator}");
+    let _ = writeln!(
+        out,
+        "| {title:<width$} | {:>10} | {:>10} | {:>10} | {:>10} |",
+        "Documented", "Percentage", "Examples", "Percentage"
+    );
+    let _ = writeln!(out, "{separator}");
+    for (name, documented, examples) in &rows {
+        row(out, name, width, *documented, *examples);
+    }
+    let _ = writeln!(out, "{separator}");
+    row(out, "Total", width, total.0, total.1);
+    let _ = writeln!(out, "{separator}");
+}
+
+fn row(
+    out: &mut String,
+    name: &str,
+    width: usize,
+    documented: CoverageCount,
+    examples: CoverageCount,
+) {
+    let examples = match examples.total {
+        0 => format!("| {:>10} | {:>10} |", "", ""),
+        _ => format!(
+            "| {:>10} | {:>9.1}% |",
+            examples.documented,
+            examples.percentage()
+        ),
+    };
+    let _ = writeln!(
+        out,
+        "| {name:<width$} | {:>10} | {:>9.1}% {examples}",
+        documented.documented,
+        documented.percentage(),
+    );
+}
+
+fn count_json(count: CoverageCount) -> Value {
+    json!({
+        "documented": count.documented,
+        "total": count.total,
+        "percentage": count.percentage(),
+    })
+}
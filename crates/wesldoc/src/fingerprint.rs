@@ -0,0 +1,100 @@
+use crate::Package;
+use anyhow::Result;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use wesldoc_ast::Version;
+
+/// Hashes everything the docs of a package are compiled from: its sources and `wesl.toml`, the
+/// options and the names and versions of all packages of the run. Dependencies are compiled from
+/// their own sources, so the docs of a package only depend on them through the versions in links.
+pub fn source_fingerprint(
+    package: &Package,
+    options: impl std::fmt::Debug,
+    packages: &[(String, Version)],
+) -> Result<String> {
+    let mut hasher = StableHasher::default();
+    hasher.write_field(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.write_field(format!("{package:?}").as_bytes());
+    hasher.write_field(format!("{options:?}").as_bytes());
+    for (name, version) in packages {
+        hasher.write_field(name.as_bytes());
+        hasher.write_field(version.to_string().as_bytes());
+    }
+    hash_sources(&package.root, &mut hasher)?;
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// Hashes the relative paths and contents of all wesl files below `root`.
+pub fn content_hash(root: &Path) -> Result<u64> {
+    let mut hasher = StableHasher::default();
+    hash_sources(root, &mut hasher)?;
+    Ok(hasher.finish())
+}
+
+fn hash_sources(root: &Path, hasher: &mut StableHasher) -> Result<()> {
+    for file in source_files(root)? {
+        let relative = file.strip_prefix(root).unwrap_or(&file);
+        for component in relative.components() {
+            hasher.write(component.as_os_str().as_encoded_bytes());
+            hasher.write(b"/");
+        }
+        hasher.write_field(&fs::read(&file)?);
+    }
+    Ok(())
+}
+
+/// All `.wesl` and `.wgsl` files below `root`, sorted by path.
+pub fn source_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !root.is_dir() {
+        return Ok(files);
+    }
+
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext == "wesl" || ext == "wgsl")
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// FNV-1a. Unlike [`std::hash::DefaultHasher`] it is stable across platforms and Rust versions,
+/// so its hashes can be stored on disk.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Writes `bytes` followed by a terminator, so consecutive fields can't run into each other.
+    fn write_field(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+        self.write(&[0]);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -1,31 +1,219 @@
 mod cargo;
+mod coverage;
+mod fingerprint;
+mod message_format;
+mod module_cache;
+mod npm;
 mod resolver;
+mod serve;
+mod standalone;
+mod timings;
+mod version;
 mod wesl_toml;
 
 use self::{
-    cargo::{CargoMetadata, CargoPackage},
+    cargo::{CargoMetadata, CargoPackage, MetadataOptions, PackageSelection},
+    coverage::{CoverageReport, coverage_table},
+    message_format::{MessageFormat, MessageSink},
+    module_cache::ModuleCache,
+    npm::{NpmMetadata, NpmPackage},
     resolver::DocsResolver,
-    wesl_toml::{DependenciesAuto, WeslToml, WeslTomlDependency},
+    timings::{PackageTimings, Timings},
+    wesl_toml::{DependenciesAuto, WeslToml, WeslTomlDependency, WeslTomlPackageManager},
 };
 use anyhow::{Context, Result, bail};
-use clap::ValueEnum;
+use clap::{FromArgMatches, Subcommand, ValueEnum};
+use rayon::prelude::*;
 use std::{
-    collections::HashMap,
-    fs,
+    collections::{BTreeMap, HashMap, HashSet, hash_map::Entry},
+    ffi::OsStr,
+    fs, mem,
     path::{Component, Path, PathBuf},
-    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 use wesl::{CompileOptions, Feature, Features, ManglerKind, ModulePath, Wesl, syntax::PathOrigin};
-use wesldoc_ast::Version;
-use wesldoc_compiler::{MissingDocumentation, WeslModule, WeslPackage};
+use wesldoc_ast::{Version, WeslDocs};
+use wesldoc_compiler::{
+    CompileOutput, Lint, LintLevel, ParsedModule, ParsedPackage, ParsedSource, WeslModule,
+    WeslModuleError, WeslPackage,
+};
+use wesldoc_generator::{GenerateOptions, api::Api, diff::ApiDiff};
 
 pub use clap::Parser;
+#[cfg(feature = "test-hooks")]
+pub use module_cache::parse_counter::parsed_modules;
+pub use serve::DocServer;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
-    /// The path to the package to generate docs for.
-    package: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate the HTML documentation.
+    Doc(DocArgs),
+    /// Compile and lint the packages without writing any documentation.
+    Check(PackageArgs),
+    /// Compare the public API of two versions of a package.
+    Diff(DiffArgs),
+    /// Check that breaking API changes between two versions come with a matching version bump.
+    SemverCheck(SemverCheckArgs),
+    /// Manage the documented versions of a package in the output directory.
+    Versions(VersionsArgs),
+    /// Generate the documentation, serve it locally and regenerate it when the sources change.
+    Serve(ServeArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct DocArgs {
+    #[command(flatten)]
+    package: PackageArgs,
+
+    /// The path to the output directory.
+    #[arg(short, long, default_value = "target/wesldoc")]
+    output: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+struct ServeArgs {
+    #[command(flatten)]
+    package: PackageArgs,
+
+    /// The path to the output directory.
+    #[arg(short, long, default_value = "target/wesldoc")]
+    output: PathBuf,
+
+    /// The address to serve the documentation on.
+    #[arg(long, default_value = "127.0.0.1:8000")]
+    address: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    /// The old version: a package directory, an `api.json` file or a documented version in the
+    /// output directory, e.g. `target/wesldoc/my_package/1.0.0`.
+    old: PathBuf,
+
+    /// The new version, same as `old`.
+    new: PathBuf,
+
+    /// The format of the report.
+    #[arg(long, value_enum, default_value = "text")]
+    format: DiffFormat,
+
+    /// Write the report to this file instead of stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct SemverCheckArgs {
+    /// The old version: a package directory, an `api.json` file or a documented version.
+    old: PathBuf,
+
+    /// The new version, same as `old`.
+    new: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+struct VersionsArgs {
+    #[command(subcommand)]
+    command: VersionsCommand,
+
+    /// The path to the output directory.
+    #[arg(short, long, default_value = "target/wesldoc", global = true)]
+    output: PathBuf,
+}
+
+#[derive(Subcommand, Debug)]
+enum VersionsCommand {
+    /// List the documented versions of a package.
+    List {
+        /// The name of the package.
+        package: String,
+    },
+    /// Remove the docs of a version.
+    Remove {
+        /// The name of the package.
+        package: String,
+        /// The version to remove.
+        version: Version,
+    },
+    /// Mark a version as yanked. `latest` does not point to yanked versions.
+    Yank {
+        /// The name of the package.
+        package: String,
+        /// The version to yank.
+        version: Version,
+        /// Undo a yank.
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Point an alias such as `next` or `dev` to a version.
+    Alias {
+        /// The name of the package.
+        package: String,
+        /// The name of the alias.
+        alias: String,
+        /// The version the alias points to. Removes the alias if omitted.
+        version: Option<Version>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DiffFormat {
+    Text,
+    Json,
+    Html,
+}
+
+/// Options shared by all subcommands that compile packages.
+#[derive(clap::Args, Debug)]
+struct PackageArgs {
+    /// The path to the package or workspace.
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// The path to `Cargo.toml`. Defaults to the `Cargo.toml` in the package path.
+    #[arg(long, value_name = "PATH")]
+    manifest_path: Option<PathBuf>,
+
+    /// Read the output of `cargo metadata --format-version 1` from this file instead of running
+    /// cargo, e.g. for hermetic builds.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["manifest_path", "offline", "frozen", "locked"]
+    )]
+    metadata_json: Option<PathBuf>,
+
+    /// Run `cargo metadata` without accessing the network.
+    #[arg(long, default_value = "false")]
+    offline: bool,
+
+    /// Require `Cargo.lock` and the cache to be up to date, implies `--offline` and `--locked`.
+    #[arg(long, default_value = "false")]
+    frozen: bool,
+
+    /// Require `Cargo.lock` to be up to date.
+    #[arg(long, default_value = "false")]
+    locked: bool,
+
+    /// Document all members of the workspace.
+    #[arg(long, default_value = "false")]
+    workspace: bool,
+
+    /// Only document this package and its dependencies. Can be given multiple times.
+    #[arg(short = 'p', long = "package", value_name = "NAME")]
+    packages: Vec<String>,
+
+    /// Exclude this workspace member. Can be given multiple times.
+    #[arg(long, value_name = "NAME")]
+    exclude: Vec<String>,
 
     /// Don't build documentation for dependencies.
     #[arg(long, default_value = "false")]
@@ -36,45 +224,400 @@ pub struct Args {
     #[arg(long)]
     max_dependency_depth: Option<usize>,
 
-    /// The path to the output directory.
-    #[arg(short, long, default_value = "target/wesldoc")]
-    output: PathBuf,
-
-    /// The missing documentation behavior.
-    #[arg(long, value_enum, default_value = "allow")]
-    missing_docs: MissingDocsArg,
+    /// The missing documentation behavior. Overrides the `missing_docs` level of `wesl.toml`,
+    /// modules and items can still override it in their sources.
+    #[arg(long, value_enum)]
+    missing_docs: Option<MissingDocsArg>,
 
-    /// Whether to print documentation statistics after compilation.
+    /// Whether to print a documentation coverage table per package after compilation.
     #[arg(long, default_value = "false")]
     statistics: bool,
+
+    /// Write the documentation coverage of all packages to this JSON file.
+    #[arg(long)]
+    coverage_json: Option<PathBuf>,
+
+    /// Fail if the documentation coverage of a package is below this percentage. Use together
+    /// with `--no-deps` to only check the package itself.
+    #[arg(long, value_name = "PERCENT")]
+    min_coverage: Option<f64>,
+
+    /// Exit with an error if any module failed to compile. Failed modules are documented with
+    /// their source and the error either way.
+    #[arg(long, default_value = "false")]
+    strict: bool,
+
+    /// Build the docs by parsing modules directly instead of compiling them with wesl. This is
+    /// faster for large dependency graphs, imports are resolved by wesldoc itself.
+    #[arg(long, default_value = "false")]
+    parse_only: bool,
+
+    /// The mangler wesl compiles modules with. The docs don't depend on it, so this is only used
+    /// to test that.
+    #[arg(long, value_enum, default_value = "escape", hide = true)]
+    mangler: ManglerArg,
+
+    /// Read and parse modules every time they are needed instead of once per run. Only used as
+    /// a baseline for benchmarks.
+    #[cfg(feature = "test-hooks")]
+    #[arg(long, default_value = "false", hide = true)]
+    no_module_cache: bool,
+
+    /// The output format of diagnostics.
+    #[arg(long, value_enum, default_value = "human")]
+    message_format: MessageFormat,
+
+    /// Print how long resolving, compiling and generating the docs of each package took.
+    #[arg(long, default_value = "false")]
+    timings: bool,
+
+    /// Link to the hosted docs of a dependency at this root URL instead of documenting it
+    /// locally. Can be given multiple times and overrides the defaults from
+    /// `[package.metadata.wesldoc.extern-html-root-urls]`.
+    #[arg(long, value_name = "PKG=URL", value_parser = parse_extern_html_root_url)]
+    extern_html_root_url: Vec<(String, String)>,
+
+    /// Don't print status messages, e.g. when the docs are only compiled for another command.
+    #[arg(skip)]
+    quiet: bool,
+
+    /// Only compile and generate the packages in these directories, e.g. when `serve` rebuilds
+    /// the packages affected by a change. They are compiled even if their docs are up to date, the
+    /// other packages are still resolved for links.
+    #[arg(skip)]
+    only: Option<Vec<PathBuf>>,
+}
+
+fn parse_extern_html_root_url(arg: &str) -> Result<(String, String)> {
+    let (package, url) = arg.split_once('=').context("expected <PKG>=<URL>")?;
+    if package.is_empty() || url.is_empty() {
+        bail!("expected <PKG>=<URL>");
+    }
+    Ok((package.to_string(), url.to_string()))
 }
 
 impl Args {
     pub fn run(self) -> Result<()> {
-        // Check Cargo.toml exist
-        if !self.package.join("Cargo.toml").is_file() {
-            bail!("Cargo.toml not found");
+        match self.command {
+            Command::Doc(args) => args.package.run(Some(&args.output)),
+            Command::Check(args) => args.run(None),
+            Command::Diff(args) => args.run(),
+            Command::SemverCheck(args) => args.run(),
+            Command::Versions(args) => args.run(),
+            Command::Serve(args) => {
+                DocServer::start(args.package, args.output, &args.address)?.wait()
+            }
         }
+    }
 
-        // Resolve cargo dependencies
-        let cargo_metadata = Rc::new(CargoMetadata::resolve(&self.package)?);
+    /// Starts `wesldoc serve` in the background instead of blocking like [`Args::run`]. The server
+    /// is stopped when the returned handle is dropped.
+    pub fn start_server(self) -> Result<DocServer> {
+        match self.command {
+            Command::Serve(args) => DocServer::start(args.package, args.output, &args.address),
+            _ => bail!("not a serve command"),
+        }
+    }
+}
 
-        // Doc packages
-        let max_depth = match self.no_deps {
-            true => 0,
-            false => self.max_dependency_depth.unwrap_or(usize::MAX),
+impl DiffArgs {
+    fn run(self) -> Result<()> {
+        let diff = load_diff(&self.old, &self.new)?;
+        let report = match self.format {
+            DiffFormat::Text => diff.to_string(),
+            DiffFormat::Json => format!("{:#}\n", serde_json::to_value(&diff)?),
+            DiffFormat::Html => wesldoc_generator::diff_html(&diff),
         };
-        for cargo_package in cargo_metadata.iter_packages(max_depth) {
-            println!(
-                "Documenting package: {} v{}",
-                cargo_package.name(),
-                cargo_package.version()
-            );
+        match &self.output {
+            Some(path) => fs::write(path, report)
+                .with_context(|| format!("failed to write report to {}", path.display()))?,
+            None => print!("{report}"),
+        }
+
+        Ok(())
+    }
+}
+
+impl SemverCheckArgs {
+    fn run(self) -> Result<()> {
+        let diff = load_diff(&self.old, &self.new)?;
+        for change in diff.breaking_changes() {
+            eprint!("breaking: {}: {}", change.item(), change.message);
+            if let (Some(old), Some(new)) = (&change.old, &change.new) {
+                eprint!(" (`{old}` -> `{new}`)");
+            }
+            eprintln!();
+        }
+
+        match diff.semver_violation() {
+            Some(violation) => Err(LintFailure(violation).into()),
+            None => {
+                println!(
+                    "{} {} -> {}: {} breaking change(s), version bump is sufficient",
+                    diff.package,
+                    diff.old_version,
+                    diff.new_version,
+                    diff.breaking_changes().count()
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+impl VersionsArgs {
+    fn run(self) -> Result<()> {
+        let output = &self.output;
+        match &self.command {
+            VersionsCommand::List { package } => {
+                for info in wesldoc_generator::versions::list_versions(output, package)? {
+                    let mut labels = Vec::new();
+                    if info.latest {
+                        labels.push("latest".to_string());
+                    }
+                    labels.extend(info.aliases.iter().cloned());
+                    if info.is_prerelease() {
+                        labels.push("pre-release".to_string());
+                    }
+                    if info.yanked {
+                        labels.push("yanked".to_string());
+                    }
+                    match labels.is_empty() {
+                        true => println!("{}", info.version),
+                        false => println!("{} ({})", info.version, labels.join(", ")),
+                    }
+                }
+            }
+            VersionsCommand::Remove { package, version } => {
+                wesldoc_generator::versions::remove_version(output, package, version)?;
+                eprintln!("Removed {package} {version}");
+            }
+            VersionsCommand::Yank {
+                package,
+                version,
+                undo,
+            } => {
+                wesldoc_generator::versions::yank_version(output, package, version, !undo)?;
+                match undo {
+                    true => eprintln!("Unyanked {package} {version}"),
+                    false => eprintln!("Yanked {package} {version}"),
+                }
+            }
+            VersionsCommand::Alias {
+                package,
+                alias,
+                version,
+            } => {
+                wesldoc_generator::versions::set_alias(output, package, alias, version.as_ref())?;
+                match version {
+                    Some(version) => eprintln!("Pointed {alias} of {package} to {version}"),
+                    None => eprintln!("Removed {alias} of {package}"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn load_diff(old: &Path, new: &Path) -> Result<ApiDiff> {
+    let old = load_api(old)?;
+    let new = load_api(new)?;
+    if old.package != new.package {
+        bail!(
+            "cannot compare different packages '{}' and '{}'",
+            old.package,
+            new.package
+        );
+    }
+    Ok(ApiDiff::between(&old, &new))
+}
+
+/// Loads the API from an `api.json` file, from a documented version that contains one, or by
+/// compiling the package in `path` without its dependencies.
+fn load_api(path: &Path) -> Result<Api> {
+    let api_json = match path.is_dir() {
+        true => path.join("api.json"),
+        false => path.to_path_buf(),
+    };
+    if api_json.is_file() {
+        return Api::load(&api_json)
+            .with_context(|| format!("failed to load api from {}", api_json.display()));
+    }
+    if !path.is_dir() {
+        bail!("{} not found", path.display());
+    }
+
+    let mut args = PackageArgs::for_path(path);
+    args.no_deps = true;
+    args.quiet = true;
+    match &args.compile(None)?[..] {
+        [docs] => Ok(Api::of(docs)),
+        [] => bail!("no wesl package found in {}", path.display()),
+        _ => bail!("{} contains more than one package", path.display()),
+    }
+}
+
+/// The run failed because of diagnostics, failed modules or missing coverage, not because
+/// wesldoc itself could not run.
+#[derive(Debug)]
+pub struct LintFailure(String);
+
+impl std::fmt::Display for LintFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for LintFailure {}
+
+/// Exit code for an error returned by [`Args::run`]: `1` if the packages failed the checks and
+/// `2` if wesldoc could not run, e.g. because of an invalid manifest.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    match err.downcast_ref::<LintFailure>() {
+        Some(_) => 1,
+        None => 2,
+    }
+}
+
+impl PackageArgs {
+    /// The default options for the package in `path`.
+    fn for_path(path: &Path) -> Self {
+        let matches = <Self as clap::Args>::augment_args(clap::Command::new("wesldoc"))
+            .get_matches_from([OsStr::new("wesldoc"), path.as_os_str()]);
+        Self::from_arg_matches(&matches).expect("default package args are valid")
+    }
+
+    fn run(self, output: Option<&Path>) -> Result<()> {
+        self.compile(output).map(drop)
+    }
+
+    /// Compiles all selected packages and writes their docs to `output`. Nothing is written if
+    /// `output` is `None`. Returns the docs of all packages without errors.
+    fn compile(&self, output: Option<&Path>) -> Result<Vec<WeslDocs>> {
+        self.build(output).result
+    }
+
+    /// Like [`Self::compile`], but also returns the printed errors and the packages of the run,
+    /// which `serve` shows and watches.
+    fn build(&self, output: Option<&Path>) -> Build {
+        let mut state = RunState::new(self.message_format);
+        #[cfg(feature = "test-hooks")]
+        if self.no_module_cache {
+            state.modules = Arc::new(ModuleCache::disabled());
+        }
+        let mut errors = Vec::new();
+        let result = self.run_packages(output, &mut state);
+        let packages = mem::take(&mut state.wesl_packages);
+        let dependencies = state
+            .dependencies
+            .iter()
+            .map(|(dir, dependencies)| {
+                let dependency_dirs = packages
+                    .iter()
+                    .filter(|package| {
+                        dependencies.iter().any(|(name, version)| {
+                            package.package_name == *name && package.version == *version
+                        })
+                    })
+                    .map(|package| package.dir.clone())
+                    .collect();
+                (dir.clone(), dependency_dirs)
+            })
+            .collect();
+        let result = match result {
+            Ok(()) => self.finish(state, &mut errors),
+            Err(err) => {
+                errors = state.sink.errors().to_vec();
+                Err(err)
+            }
+        };
+
+        Build {
+            result,
+            errors,
+            packages,
+            dependencies,
+        }
+    }
+
+    fn run_packages(&self, output: Option<&Path>, state: &mut RunState) -> Result<()> {
+        state
+            .generate_options
+            .extern_html_root_urls
+            .extend(self.extern_html_root_url.iter().cloned());
+        if self.manifest_path.is_some() || self.metadata_json.is_some() {
+            return self.run_cargo(output, state);
+        }
+
+        let (wesl_toml, has_wesl_toml_file) = load_wesl_toml(self.path.join("wesl.toml"))?;
+        let has_cargo_toml = self.path.join("Cargo.toml").is_file();
+        let has_package_json = self.path.join("package.json").is_file();
+        match wesl_toml.package.package_manager {
+            Some(WeslTomlPackageManager::Cargo) if has_cargo_toml => self.run_cargo(output, state),
+            Some(WeslTomlPackageManager::Npm) if has_package_json => self.run_npm(output, state),
+            Some(WeslTomlPackageManager::Cargo) => bail!("Cargo.toml not found"),
+            Some(WeslTomlPackageManager::Npm) => bail!("package.json not found"),
+            None if has_cargo_toml => self.run_cargo(output, state),
+            None if has_package_json => self.run_npm(output, state),
+            None if has_wesl_toml_file => self.run_standalone(output, state),
+            None => bail!("neither Cargo.toml, package.json nor wesl.toml found"),
+        }
+    }
+
+    fn run_cargo(&self, output: Option<&Path>, state: &mut RunState) -> Result<()> {
+        // Resolve cargo dependencies and select the packages to document
+        let started = Instant::now();
+        let cargo_metadata = Arc::new(match &self.metadata_json {
+            Some(path) => CargoMetadata::from_json_file(path)?,
+            None => CargoMetadata::resolve(&MetadataOptions {
+                manifest_path: self
+                    .manifest_path
+                    .clone()
+                    .unwrap_or_else(|| self.path.join("Cargo.toml")),
+                offline: self.offline,
+                frozen: self.frozen,
+                locked: self.locked,
+            })?,
+        });
+        state.timings.resolve = started.elapsed();
+        let roots = cargo_metadata.select(&PackageSelection {
+            workspace: self.workspace,
+            packages: self.packages.clone(),
+            exclude: self.exclude.clone(),
+        })?;
+
+        // Hosted docs from the selected packages, command line options take precedence
+        for root in &roots {
+            for (package, url) in root.extern_html_root_urls()? {
+                state
+                    .generate_options
+                    .extern_html_root_urls
+                    .entry(package)
+                    .or_insert(url);
+            }
+        }
+
+        // Doc packages
+        let cargo_packages = cargo_metadata
+            .iter_packages(&roots, self.max_depth())
+            .collect::<Vec<_>>();
+        state.packages = cargo_packages
+            .iter()
+            .map(|package| (package.name(), package.version()))
+            .collect();
+        let mut jobs = Vec::new();
+        let mut path_dependencies = Vec::new();
+        for cargo_package in cargo_packages {
+            if self.is_extern(&cargo_package.name(), state) {
+                continue;
+            }
+            self.package_status(output, &cargo_package.name(), &cargo_package.version());
 
             // Package from cargo package and check if it is a wesl package
             let package = Package::from_cargo_package(cargo_package, None)?;
             if !is_wesl_package(&package)? {
-                println!("No wesl files found, skipping package");
+                self.status("No wesl files found, skipping package");
                 continue;
             }
 
@@ -82,8 +625,9 @@ impl Args {
             let resolver = match package.wesl_toml.package.dependencies {
                 Some(DependenciesAuto::Auto) => DocsResolver::new_auto(
                     &package,
-                    Rc::clone(&cargo_metadata),
+                    Arc::clone(&cargo_metadata),
                     cargo_package.clone(),
+                    Arc::clone(&state.modules),
                 ),
                 None => {
                     let dependencies = package
@@ -99,34 +643,630 @@ impl Args {
                             )
                         })
                         .collect::<Result<Vec<_>>>()?;
-                    DocsResolver::new_explicit(&package, dependencies)
+                    path_dependencies.extend(
+                        package
+                            .wesl_toml
+                            .dependencies
+                            .values()
+                            .zip(&dependencies)
+                            .filter(|(dep, _)| dep.path.is_some())
+                            .map(|(_, dependency)| dependency.clone()),
+                    );
+                    DocsResolver::new_explicit(&package, dependencies, Arc::clone(&state.modules))
                 }
             };
 
-            // Compile to wesl
-            let wesl_package = compile_package(package, resolver)?;
+            let is_root = roots
+                .iter()
+                .any(|root| root.crate_path() == cargo_package.crate_path());
+            jobs.push(PackageJob {
+                package,
+                resolver,
+                is_root,
+            });
+        }
+        let path_jobs = self.path_dependency_jobs(path_dependencies, &jobs, output, state)?;
+        jobs.extend(path_jobs);
+        let documented_roots = jobs
+            .iter()
+            .map(|job| (job.is_root, job.package.package_name.clone()))
+            .zip(self.document_all(jobs, output, state)?)
+            .filter(|((is_root, _), documented)| *is_root && *documented)
+            .map(|((_, name), _)| name)
+            .collect::<Vec<_>>();
 
-            // Compile to docs
-            let (docs, compile_stats) = wesldoc_compiler::compile(
-                &wesl_package,
-                &wesldoc_compiler::CompileOptions {
-                    missing_documentation: self.missing_docs.into(),
-                },
-            )
-            .with_context(|| format!("failed to compile package '{}'", wesl_package.root.name))?;
-            if self.statistics {
-                println!(
-                    "Documentation Coverage: {:.2}%",
-                    compile_stats.documented_percentage()
-                );
+        // List the documented workspace members on the index page
+        if let Some(output) = output
+            && (self.workspace || cargo_metadata.is_virtual())
+        {
+            wesldoc_generator::set_workspace_members(output, &documented_roots)?;
+        }
+
+        Ok(())
+    }
+
+    fn run_npm(&self, output: Option<&Path>, state: &mut RunState) -> Result<()> {
+        if self.workspace || !self.packages.is_empty() || !self.exclude.is_empty() {
+            bail!("package selection is only supported for cargo workspaces");
+        }
+
+        // Resolve the installed node_modules tree
+        let started = Instant::now();
+        let npm_metadata = Arc::new(NpmMetadata::resolve(&self.path)?);
+        state.timings.resolve = started.elapsed();
+
+        let npm_packages = npm_metadata.iter_packages(self.max_depth());
+        state.packages = npm_packages
+            .iter()
+            .map(|package| (package.name(), package.version()))
+            .collect();
+        let mut jobs = Vec::new();
+        let mut path_dependencies = Vec::new();
+        for npm_package in npm_packages {
+            if self.is_extern(&npm_package.name(), state) {
+                continue;
+            }
+            self.package_status(output, &npm_package.name(), &npm_package.version());
+
+            let package = Package::from_npm_package(npm_package, None)?;
+            if !is_wesl_package(&package)? {
+                self.status("No wesl files found, skipping package");
+                continue;
+            }
+
+            let resolver = match package.wesl_toml.package.dependencies {
+                Some(DependenciesAuto::Auto) => DocsResolver::new_auto_npm(
+                    &package,
+                    Arc::clone(&npm_metadata),
+                    npm_package.clone(),
+                    Arc::clone(&state.modules),
+                ),
+                None => {
+                    let dependencies = package
+                        .wesl_toml
+                        .dependencies
+                        .iter()
+                        .map(|(dep_key, dep)| {
+                            Package::new_npm_dependency(
+                                npm_package,
+                                dep_key,
+                                Some(dep),
+                                &npm_metadata,
+                            )
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    path_dependencies.extend(
+                        package
+                            .wesl_toml
+                            .dependencies
+                            .values()
+                            .zip(&dependencies)
+                            .filter(|(dep, _)| dep.path.is_some())
+                            .map(|(_, dependency)| dependency.clone()),
+                    );
+                    DocsResolver::new_explicit(&package, dependencies, Arc::clone(&state.modules))
+                }
+            };
+            let is_root = npm_package.package_path() == npm_metadata.root().package_path();
+            jobs.push(PackageJob {
+                package,
+                resolver,
+                is_root,
+            });
+        }
+        let path_jobs = self.path_dependency_jobs(path_dependencies, &jobs, output, state)?;
+        jobs.extend(path_jobs);
+        self.document_all(jobs, output, state)?;
+
+        Ok(())
+    }
+
+    fn run_standalone(&self, output: Option<&Path>, state: &mut RunState) -> Result<()> {
+        if self.workspace || !self.packages.is_empty() || !self.exclude.is_empty() {
+            bail!("package selection is only supported for cargo workspaces");
+        }
+
+        let started = Instant::now();
+        let standalone_packages = standalone::resolve(&self.path, self.max_depth())?;
+        state.timings.resolve = started.elapsed();
+        state.packages = standalone_packages
+            .iter()
+            .map(|standalone| {
+                let package = &standalone.package;
+                (package.package_name.clone(), package.version.clone())
+            })
+            .collect();
+        // The package in the given path is resolved first
+        let mut jobs = Vec::new();
+        for (idx, standalone) in standalone_packages.into_iter().enumerate() {
+            let package = standalone.package;
+            if self.is_extern(&package.package_name, state) {
+                continue;
+            }
+            self.package_status(output, &package.package_name, &package.version);
+            if !is_wesl_package(&package)? {
+                self.status("No wesl files found, skipping package");
+                continue;
             }
 
-            // Generate docs
-            wesldoc_generator::generate(&docs, &self.output)?;
+            let resolver = DocsResolver::new_explicit(
+                &package,
+                standalone.dependencies,
+                Arc::clone(&state.modules),
+            );
+            jobs.push(PackageJob {
+                package,
+                resolver,
+                is_root: idx == 0,
+            });
         }
+        self.document_all(jobs, output, state)?;
 
         Ok(())
     }
+
+    /// Jobs for the path dependencies declared in `wesl.toml` files, which the package manager
+    /// does not know about. They are resolved like standalone packages, so their own dependencies
+    /// must be path dependencies too. Packages that already have a job are skipped.
+    fn path_dependency_jobs(
+        &self,
+        mut pending: Vec<Package>,
+        jobs: &[PackageJob],
+        output: Option<&Path>,
+        state: &mut RunState,
+    ) -> Result<Vec<PackageJob>> {
+        let mut path_jobs = Vec::new();
+        if self.max_depth() == 0 {
+            return Ok(path_jobs);
+        }
+
+        let visited = jobs
+            .iter()
+            .filter_map(|job| job.package.dir.canonicalize().ok())
+            .collect::<HashSet<_>>();
+        pending.sort_by(|a, b| a.dir.cmp(&b.dir));
+        // The pending packages are already one level below the packages of the jobs
+        let standalone_packages = standalone::resolve_all(pending, visited, self.max_depth() - 1)?;
+        for standalone in standalone_packages {
+            let package = standalone.package;
+            if self.is_extern(&package.package_name, state) {
+                continue;
+            }
+            self.package_status(output, &package.package_name, &package.version);
+            if !is_wesl_package(&package)? {
+                self.status("No wesl files found, skipping package");
+                continue;
+            }
+
+            state
+                .packages
+                .push((package.package_name.clone(), package.version.clone()));
+            let resolver = DocsResolver::new_explicit(
+                &package,
+                standalone.dependencies,
+                Arc::clone(&state.modules),
+            );
+            path_jobs.push(PackageJob {
+                package,
+                resolver,
+                is_root: false,
+            });
+        }
+
+        Ok(path_jobs)
+    }
+
+    /// Compiles the packages concurrently, then reports their diagnostics and generates their
+    /// docs in order. Returns for each package whether its docs are available, i.e. it has no
+    /// errors. Dependencies that did not change since their docs were generated are skipped,
+    /// selected packages and the ones in [`Self::only`] are always compiled to report their
+    /// diagnostics.
+    fn document_all(
+        &self,
+        jobs: Vec<PackageJob>,
+        output: Option<&Path>,
+        state: &mut RunState,
+    ) -> Result<Vec<bool>> {
+        let mut documented = vec![true; jobs.len()];
+
+        // Skip unchanged dependencies
+        let mut compile_jobs = Vec::new();
+        for (idx, job) in jobs.into_iter().enumerate() {
+            state.wesl_packages.push(job.package.clone());
+            if let Some(only) = &self.only
+                && !only.contains(&job.package.dir)
+            {
+                continue;
+            }
+
+            let source_fingerprint = match output {
+                Some(_) => Some(fingerprint::source_fingerprint(
+                    &job.package,
+                    (
+                        self.parse_only,
+                        self.missing_docs,
+                        state
+                            .generate_options
+                            .extern_html_root_urls
+                            .iter()
+                            .collect::<BTreeMap<_, _>>(),
+                    ),
+                    &state.packages,
+                )?),
+                None => None,
+            };
+            if let (Some(output), Some(fingerprint)) = (output, &source_fingerprint)
+                && !job.is_root
+                && self.only.is_none()
+                && !self.reports_coverage()
+                && wesldoc_generator::is_up_to_date(
+                    output,
+                    &job.package.package_name,
+                    &job.package.version,
+                    fingerprint,
+                )
+            {
+                self.status(format_args!(
+                    "Skipping unchanged package: {} v{}",
+                    job.package.package_name, job.package.version
+                ));
+                continue;
+            }
+            compile_jobs.push((idx, job, source_fingerprint));
+        }
+
+        // Compile concurrently, packages are compiled from their own sources
+        let compiled = compile_jobs
+            .into_par_iter()
+            .map(|(idx, job, source_fingerprint)| {
+                (idx, self.compile_package(job), source_fingerprint)
+            })
+            .collect::<Vec<_>>();
+
+        // Report and generate in order
+        for (idx, compiled, source_fingerprint) in compiled {
+            documented[idx] = self.document(compiled?, source_fingerprint, output, state)?;
+        }
+
+        Ok(documented)
+    }
+
+    fn compile_package(&self, job: PackageJob) -> Result<CompiledPackage> {
+        let started = Instant::now();
+        let PackageJob {
+            package, resolver, ..
+        } = job;
+        let name = package.package_name.clone();
+        let version = package.version.clone();
+        let dir = package.dir.clone();
+        let root = package.root.clone();
+
+        let mut lints = package.wesl_toml.lint_levels()?;
+        if let Some(missing_docs) = self.missing_docs {
+            lints.set(Lint::MissingDocs, missing_docs.into());
+        }
+        let compile_options = wesldoc_compiler::CompileOptions { lints };
+        let mut failures = Vec::new();
+        let (output, dependencies) = if self.parse_only {
+            let parsed_package = parse_package(package, resolver, &mut failures)?;
+            (
+                wesldoc_compiler::compile_parsed(&parsed_package, &compile_options),
+                parsed_package.dependencies.into_values().collect(),
+            )
+        } else {
+            let wesl_package =
+                compile_package(package, resolver, self.mangler.into(), &mut failures)?;
+            (
+                wesldoc_compiler::compile(&wesl_package, &compile_options),
+                wesl_package.dependencies.into_values().collect(),
+            )
+        };
+
+        Ok(CompiledPackage {
+            name,
+            version,
+            dir,
+            root,
+            dependencies,
+            output,
+            failures,
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Reports the diagnostics of a compiled package and generates its docs. Returns `false` if
+    /// the package has errors and no docs were generated.
+    fn document(
+        &self,
+        compiled: CompiledPackage,
+        source_fingerprint: Option<String>,
+        output: Option<&Path>,
+        state: &mut RunState,
+    ) -> Result<bool> {
+        let CompiledPackage {
+            name: package_name,
+            version: package_version,
+            dir: package_dir,
+            root: package_root,
+            dependencies,
+            output: compiled,
+            failures,
+            duration: compile_duration,
+        } = compiled;
+        state.failures.extend(failures);
+        state.dependencies.push((package_dir, dependencies));
+        state
+            .sink
+            .diagnostics(&package_name, &package_root, &compiled);
+        state.error_count += compiled.error_count();
+        state.warning_count += compiled.warning_count();
+        if self.statistics {
+            self.status(coverage_table(&compiled.coverage));
+        }
+        state
+            .coverage_report
+            .add(&package_name, &package_version, &compiled.coverage);
+        let coverage = compiled.coverage.total().percentage();
+        if self.min_coverage.is_some_and(|min| coverage < min) {
+            state
+                .below_min_coverage
+                .push(format!("{package_name} ({coverage:.1}%)"));
+        }
+        if compiled.error_count() > 0 {
+            state.failed_packages += 1;
+            if output.is_some() {
+                eprintln!("Not generating docs for package '{package_name}' due to errors");
+            }
+            return Ok(false);
+        }
+
+        // Generate docs
+        let started = Instant::now();
+        if let Some(output) = output {
+            state.generate_options.source_fingerprint = source_fingerprint;
+            wesldoc_generator::generate(&compiled.docs, output, &state.generate_options)?;
+        }
+        state.timings.packages.push(PackageTimings {
+            name: package_name,
+            version: package_version,
+            compile: compile_duration,
+            generate: started.elapsed(),
+        });
+        state.docs.push(compiled.docs);
+
+        Ok(true)
+    }
+
+    /// Reports what was collected during the run. The errors and compile failures as they are
+    /// shown to humans are stored in `errors`.
+    fn finish(&self, state: RunState, errors: &mut Vec<String>) -> Result<Vec<WeslDocs>> {
+        let RunState {
+            mut sink,
+            generate_options: _,
+            packages: _,
+            wesl_packages: _,
+            dependencies: _,
+            modules: _,
+            started,
+            timings,
+            docs,
+            failures,
+            coverage_report,
+            below_min_coverage,
+            error_count,
+            warning_count,
+            failed_packages,
+        } = state;
+
+        // Report modules that failed to compile
+        sink.compile_failures(&failures);
+        *errors = sink.errors().to_vec();
+        sink.finish();
+        if self.timings {
+            self.status(timings.table(started.elapsed()).trim_end());
+        }
+
+        // Summarize diagnostics and write reports before failing the run
+        if error_count > 0 || warning_count > 0 {
+            eprintln!(
+                "{}, {}",
+                plural(error_count, "error"),
+                plural(warning_count, "warning")
+            );
+        }
+        if let Some(path) = &self.coverage_json {
+            fs::write(path, format!("{:#}", coverage_report.to_json()))
+                .with_context(|| format!("failed to write coverage to {}", path.display()))?;
+        }
+
+        if self.strict && !failures.is_empty() {
+            return Err(
+                LintFailure(format!("{} module(s) failed to compile", failures.len())).into(),
+            );
+        }
+        if let Some(min_coverage) = self.min_coverage
+            && !below_min_coverage.is_empty()
+        {
+            return Err(LintFailure(format!(
+                "documentation coverage is below {min_coverage}% for {}",
+                below_min_coverage.join(", ")
+            ))
+            .into());
+        }
+        if failed_packages > 0 {
+            return Err(LintFailure(format!(
+                "{} failed with errors",
+                plural(failed_packages, "package")
+            ))
+            .into());
+        }
+
+        Ok(docs)
+    }
+
+    /// Whether the coverage of every package is needed, which requires compiling all of them.
+    fn reports_coverage(&self) -> bool {
+        self.statistics || self.coverage_json.is_some() || self.min_coverage.is_some()
+    }
+
+    fn max_depth(&self) -> usize {
+        match self.no_deps {
+            true => 0,
+            false => self.max_dependency_depth.unwrap_or(usize::MAX),
+        }
+    }
+
+    /// Whether the package is hosted elsewhere and should not be documented locally.
+    fn is_extern(&self, name: &str, state: &RunState) -> bool {
+        match state.generate_options.extern_html_root_urls.get(name) {
+            Some(url) => {
+                self.status(format_args!("Skipping package: {name}, linking to {url}"));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn package_status(&self, output: Option<&Path>, name: &str, version: &Version) {
+        self.status(format_args!(
+            "{} package: {name} v{version}",
+            match output {
+                Some(_) => "Documenting",
+                None => "Checking",
+            },
+        ));
+    }
+}
+
+/// The outcome of documenting the packages of a run, see [`PackageArgs::build`].
+struct Build {
+    result: Result<Vec<WeslDocs>>,
+    /// The errors and compile failures as they were shown to humans.
+    errors: Vec<String>,
+    /// All WESL packages of the run.
+    packages: Vec<Package>,
+    /// The directories of the dependencies of every compiled package, keyed by its directory.
+    dependencies: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+/// A package that is ready to be compiled.
+struct PackageJob {
+    package: Package,
+    resolver: DocsResolver,
+    /// Whether the package was selected, as opposed to being a dependency.
+    is_root: bool,
+}
+
+struct CompiledPackage {
+    name: String,
+    version: Version,
+    dir: PathBuf,
+    root: PathBuf,
+    /// The names and versions of the dependencies the package imports from.
+    dependencies: Vec<(String, Version)>,
+    output: CompileOutput,
+    failures: Vec<CompileFailure>,
+    duration: Duration,
+}
+
+/// Results collected while documenting all packages of a run.
+struct RunState {
+    sink: MessageSink,
+    generate_options: GenerateOptions,
+    /// The names and versions of all packages of the run.
+    packages: Vec<(String, Version)>,
+    /// All WESL packages of the run, including the ones that were skipped.
+    wesl_packages: Vec<Package>,
+    /// The directory of every compiled package with the names and versions of its dependencies.
+    dependencies: Vec<(PathBuf, Vec<(String, Version)>)>,
+    /// Modules read by the resolvers of all packages.
+    modules: Arc<ModuleCache>,
+    started: Instant,
+    timings: Timings,
+    docs: Vec<WeslDocs>,
+    failures: Vec<CompileFailure>,
+    coverage_report: CoverageReport,
+    below_min_coverage: Vec<String>,
+    error_count: usize,
+    warning_count: usize,
+    failed_packages: usize,
+}
+
+impl RunState {
+    fn new(message_format: MessageFormat) -> Self {
+        Self {
+            sink: MessageSink::new(message_format),
+            generate_options: GenerateOptions::default(),
+            packages: Vec::new(),
+            wesl_packages: Vec::new(),
+            dependencies: Vec::new(),
+            modules: Arc::default(),
+            started: Instant::now(),
+            timings: Timings::default(),
+            docs: Vec::new(),
+            failures: Vec::new(),
+            coverage_report: CoverageReport::default(),
+            below_min_coverage: Vec::new(),
+            error_count: 0,
+            warning_count: 0,
+            failed_packages: 0,
+        }
+    }
+}
+
+impl PackageArgs {
+    // Status messages go to stderr if stdout is used for machine readable output.
+    fn status(&self, message: impl std::fmt::Display) {
+        if self.quiet {
+            return;
+        }
+        match self.message_format {
+            MessageFormat::Human => println!("{message}"),
+            MessageFormat::Json | MessageFormat::Sarif => eprintln!("{message}"),
+        }
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("{count} {word}"),
+        _ => format!("{count} {word}s"),
+    }
+}
+
+#[derive(Debug)]
+struct CompileFailure {
+    package: String,
+    module: ModulePath,
+    file: PathBuf,
+    message: String,
+}
+
+impl std::fmt::Display for CompileFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "error in {}::{}:",
+            self.package,
+            self.module.components.join("::")
+        )?;
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ManglerArg {
+    Escape,
+    Hash,
+}
+
+impl From<ManglerArg> for ManglerKind {
+    fn from(arg: ManglerArg) -> Self {
+        match arg {
+            ManglerArg::Escape => ManglerKind::Escape,
+            ManglerArg::Hash => ManglerKind::Hash,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -139,12 +1279,12 @@ enum MissingDocsArg {
     Deny,
 }
 
-impl From<MissingDocsArg> for MissingDocumentation {
+impl From<MissingDocsArg> for LintLevel {
     fn from(arg: MissingDocsArg) -> Self {
         match arg {
-            MissingDocsArg::Allow => MissingDocumentation::Allow,
-            MissingDocsArg::Warn => MissingDocumentation::Warn,
-            MissingDocsArg::Deny => MissingDocumentation::Deny,
+            MissingDocsArg::Allow => LintLevel::Allow,
+            MissingDocsArg::Warn => LintLevel::Warn,
+            MissingDocsArg::Deny => LintLevel::Deny,
         }
     }
 }
@@ -155,30 +1295,20 @@ fn is_wesl_package(package: &Package) -> Result<bool> {
         return Ok(true);
     }
 
-    if !package.root.is_dir() {
-        return Ok(false);
-    }
-    let mut dirs = vec![package.root.clone()];
-    while let Some(dir) = dirs.pop() {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_file() && path.extension().is_some_and(|ext| ext == "wesl") {
-                return Ok(true);
-            } else if path.is_dir() {
-                dirs.push(path);
-            }
-        }
-    }
-
-    Ok(false)
+    Ok(fingerprint::source_files(&package.root)?
+        .iter()
+        .any(|path| path.extension().is_some_and(|ext| ext == "wesl")))
 }
 
-fn compile_package(package: Package, resolver: DocsResolver) -> Result<WeslPackage> {
+fn compile_package(
+    package: Package,
+    resolver: DocsResolver,
+    mangler: ManglerKind,
+    failures: &mut Vec<CompileFailure>,
+) -> Result<WeslPackage> {
     let wesl = {
         let mut wesl = Wesl::new_barebones().set_custom_resolver(resolver);
-        wesl.set_mangler(ManglerKind::Escape)
+        wesl.set_mangler(mangler)
             .use_sourcemap(true)
             .set_options(CompileOptions {
                 imports: true,
@@ -201,9 +1331,10 @@ fn compile_package(package: Package, resolver: DocsResolver) -> Result<WeslPacka
 
     // Compile root and submodules
     let root = WeslModule {
-        name: package.package_name,
+        name: package.package_name.clone(),
         compiled: None,
-        submodules: compile_submodules(&wesl, &package.root, &package.root)?,
+        error: None,
+        submodules: compile_submodules(&wesl, &package, &package.root, failures)?,
     };
 
     // Get resolved dependencies
@@ -218,14 +1349,143 @@ fn compile_package(package: Package, resolver: DocsResolver) -> Result<WeslPacka
         version: package.version,
         dependencies,
         root,
+        sources: wesl.resolver().resolved_sources(),
     })
 }
 
+/// Compiles the modules in `dir` concurrently. Failures are collected per entry and appended in
+/// the order of the directory entries.
 fn compile_submodules(
     wesl: &Wesl<DocsResolver>,
+    package: &Package,
     dir: &Path,
-    root: &Path,
+    failures: &mut Vec<CompileFailure>,
 ) -> Result<Vec<WeslModule>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let paths = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    let entries = paths
+        .into_par_iter()
+        .map(|path| {
+            let mut entry_failures = Vec::new();
+            let module = compile_entry(wesl, package, &path, &mut entry_failures)?;
+            Ok((module, entry_failures))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // A module can have both a file and a directory of submodules
+    let mut submodules = HashMap::<String, WeslModule>::new();
+    for (module, entry_failures) in entries {
+        failures.extend(entry_failures);
+        let Some(module) = module else {
+            continue;
+        };
+        match submodules.entry(module.name.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(module);
+            }
+            Entry::Occupied(entry) => {
+                let sub = entry.into_mut();
+                sub.compiled = sub.compiled.take().or(module.compiled);
+                sub.error = sub.error.take().or(module.error);
+                sub.submodules.extend(module.submodules);
+            }
+        }
+    }
+
+    Ok(submodules
+        .into_values()
+        .filter(|module| {
+            module.compiled.is_some() || module.error.is_some() || !module.submodules.is_empty()
+        })
+        .collect())
+}
+
+/// Compiles the module of a wesl file, or the submodules of a directory. Other files are skipped.
+fn compile_entry(
+    wesl: &Wesl<DocsResolver>,
+    package: &Package,
+    path: &Path,
+    failures: &mut Vec<CompileFailure>,
+) -> Result<Option<WeslModule>> {
+    let mut module = WeslModule {
+        name: name_from_path(path)?,
+        compiled: None,
+        error: None,
+        submodules: Vec::new(),
+    };
+
+    if path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext == "wesl" || ext == "wgsl")
+    {
+        let module_path = module_path_from_file(&package.root, path)?;
+        let compile_result = wesl.compile(&module_path);
+        let root_file_imports = wesl.resolver().root_file_imports(&module_path);
+        match compile_result {
+            Ok(compile_result) => module.compiled = Some((root_file_imports, compile_result)),
+            Err(err) => {
+                let message = err.to_string();
+                failures.push(CompileFailure {
+                    package: package.package_name.clone(),
+                    module: module_path,
+                    file: path.to_path_buf(),
+                    message: message.clone(),
+                });
+                module.error = Some(WeslModuleError {
+                    message,
+                    source: fs::read_to_string(path).ok(),
+                });
+            }
+        }
+    } else if path.is_dir() {
+        module.submodules = compile_submodules(wesl, package, path, failures)?;
+    } else {
+        return Ok(None);
+    }
+
+    Ok(Some(module))
+}
+
+fn parse_package(
+    package: Package,
+    resolver: DocsResolver,
+    failures: &mut Vec<CompileFailure>,
+) -> Result<ParsedPackage> {
+    let mut parsed_package = ParsedPackage {
+        version: package.version.clone(),
+        dependencies: HashMap::new(),
+        root: ParsedModule {
+            name: package.package_name.clone(),
+            parsed: None,
+            error: None,
+            submodules: parse_submodules(&package, &resolver, &package.root, failures)?,
+        },
+        dependency_sources: Default::default(),
+    };
+
+    // Load imported dependency modules, this also resolves the dependencies
+    parsed_package.load_dependencies(&resolver);
+    parsed_package.dependencies = resolver
+        .resolved_dependencies()
+        .into_iter()
+        .map(|dep| (dep.local_name, (dep.package_name, dep.version)))
+        .collect();
+
+    Ok(parsed_package)
+}
+
+fn parse_submodules(
+    package: &Package,
+    resolver: &DocsResolver,
+    dir: &Path,
+    failures: &mut Vec<CompileFailure>,
+) -> Result<Vec<ParsedModule>> {
     let mut submodules = HashMap::new();
 
     if !dir.is_dir() {
@@ -245,51 +1505,78 @@ fn compile_submodules(
         {
             let sub = submodules
                 .entry(name)
-                .or_insert_with_key(|name| WeslModule {
+                .or_insert_with_key(|name| ParsedModule {
                     name: name.clone(),
-                    compiled: None,
+                    parsed: None,
+                    error: None,
                     submodules: Vec::new(),
                 });
 
-            let compile_result = wesl.compile(&ModulePath {
-                origin: PathOrigin::Absolute,
-                components: path
-                    .strip_prefix(root)?
-                    .components()
-                    .map(|part| match part {
-                        Component::Normal(name) => {
-                            let name = name.to_string_lossy().to_string();
-                            let name = name
-                                .strip_suffix(".wesl")
-                                .or_else(|| name.strip_suffix(".wgsl"))
-                                .map(|s| s.to_string())
-                                .unwrap_or(name);
-                            Ok(name)
-                        }
-                        _ => bail!("unexpected path component"),
+            let module_path = module_path_from_file(&package.root, &path)?;
+            let module = resolver.module(&module_path)?;
+            match module.syntax() {
+                Ok(syntax) => {
+                    sub.parsed = Some(ParsedSource {
+                        source: module.source.clone(),
+                        syntax: syntax.clone(),
                     })
-                    .collect::<Result<_>>()?,
-            })?;
-            let root_file_imports = wesl.resolver().take_root_file_imports();
-            sub.compiled = Some((root_file_imports, compile_result))
+                }
+                Err(message) => {
+                    failures.push(CompileFailure {
+                        package: package.package_name.clone(),
+                        module: module_path,
+                        file: path.clone(),
+                        message: message.to_string(),
+                    });
+                    sub.error = Some(WeslModuleError {
+                        message: message.to_string(),
+                        source: Some(module.source.clone()),
+                    });
+                }
+            }
         } else if path.is_dir() {
             let sub = submodules
                 .entry(name)
-                .or_insert_with_key(|name| WeslModule {
+                .or_insert_with_key(|name| ParsedModule {
                     name: name.clone(),
-                    compiled: None,
+                    parsed: None,
+                    error: None,
                     submodules: Vec::new(),
                 });
-            sub.submodules = compile_submodules(wesl, &path, root)?;
+            sub.submodules = parse_submodules(package, resolver, &path, failures)?;
         }
     }
 
     Ok(submodules
         .into_values()
-        .filter(|module| module.compiled.is_some() || !module.submodules.is_empty())
+        .filter(|module| {
+            module.parsed.is_some() || module.error.is_some() || !module.submodules.is_empty()
+        })
         .collect())
 }
 
+fn module_path_from_file(root: &Path, path: &Path) -> Result<ModulePath> {
+    Ok(ModulePath {
+        origin: PathOrigin::Absolute,
+        components: path
+            .strip_prefix(root)?
+            .components()
+            .map(|part| match part {
+                Component::Normal(name) => {
+                    let name = name.to_string_lossy().to_string();
+                    let name = name
+                        .strip_suffix(".wesl")
+                        .or_else(|| name.strip_suffix(".wgsl"))
+                        .map(|s| s.to_string())
+                        .unwrap_or(name);
+                    Ok(name)
+                }
+                _ => bail!("unexpected path component"),
+            })
+            .collect::<Result<_>>()?,
+    })
+}
+
 #[derive(Debug, Clone)]
 struct Package {
     local_name: String,
@@ -297,6 +1584,8 @@ struct Package {
     version: Version,
     wesl_toml: WeslToml,
     has_wesl_toml_file: bool,
+    /// The directory of the package manifest, `root` is relative to it.
+    dir: PathBuf,
     root: PathBuf,
 }
 
@@ -319,10 +1608,42 @@ impl Package {
             version,
             wesl_toml,
             has_wesl_toml_file,
+            dir: cargo_package.crate_path(),
             root,
         })
     }
 
+    /// Loads a standalone package from its `wesl.toml`, which must set a name and a version.
+    fn from_wesl_toml_dir(dir: &Path, local_name: Option<String>) -> Result<Self> {
+        let (wesl_toml, has_wesl_toml_file) = load_wesl_toml(dir.join("wesl.toml"))?;
+        if !has_wesl_toml_file {
+            bail!("wesl.toml not found in {}", dir.display());
+        }
+
+        let package_name = wesl_toml.package.name.clone().with_context(|| {
+            format!(
+                "missing package name in {}",
+                dir.join("wesl.toml").display()
+            )
+        })?;
+        let version = wesl_toml.package.version()?.with_context(|| {
+            format!(
+                "missing package version in {}",
+                dir.join("wesl.toml").display()
+            )
+        })?;
+
+        Ok(Self {
+            local_name: local_name.unwrap_or_else(|| package_name.clone()),
+            package_name,
+            version,
+            root: dir.join(&wesl_toml.package.root),
+            dir: dir.to_path_buf(),
+            wesl_toml,
+            has_wesl_toml_file,
+        })
+    }
+
     fn new_dependency(
         this_cargo_package: &CargoPackage,
 
@@ -337,18 +1658,11 @@ impl Package {
 
         // Handle path dependencies
         if let Some(dep_path) = dependency.and_then(|d| d.path.as_ref()) {
-            let dep_path = this_cargo_package.crate_path().join(dep_path);
-            let (dep_wesl_toml, dep_has_wesl_toml_file) =
-                load_wesl_toml(dep_path.join("wesl.toml"))?;
-
-            return Ok(Package {
-                local_name: dependency_key.clone(),
-                package_name: dep_name.clone(),
-                root: dep_path.join(&dep_wesl_toml.package.root),
-                wesl_toml: dep_wesl_toml,
-                has_wesl_toml_file: dep_has_wesl_toml_file,
-                version: Version::new(0, 0, 0), // TODO: path dependencies don't have versions
-            });
+            return Package::new_path_dependency(
+                &this_cargo_package.crate_path().join(dep_path),
+                dependency_key,
+                dep_name.clone(),
+            );
         }
 
         let dep_pkg_id = this_cargo_package
@@ -359,6 +1673,75 @@ impl Package {
             .context("invalid dependency")?;
         Package::from_cargo_package(dep_pkg, Some(dependency_key))
     }
+
+    fn from_npm_package(npm_package: &NpmPackage, local_name: Option<String>) -> Result<Self> {
+        let (wesl_toml, has_wesl_toml_file) =
+            load_wesl_toml(npm_package.package_path().join("wesl.toml"))?;
+
+        let local_name = local_name.unwrap_or_else(|| npm_package.name());
+        let package_name = npm_package.name();
+        let version = npm_package.version();
+        let root = npm_package.package_path().join(&wesl_toml.package.root);
+
+        Ok(Self {
+            local_name,
+            package_name,
+            version,
+            wesl_toml,
+            has_wesl_toml_file,
+            dir: npm_package.package_path(),
+            root,
+        })
+    }
+
+    fn new_npm_dependency(
+        this_npm_package: &NpmPackage,
+
+        dependency_key: impl Into<String>,
+        dependency: Option<&WeslTomlDependency>,
+        npm_metadata: &NpmMetadata,
+    ) -> Result<Self> {
+        let dependency_key = dependency_key.into();
+        let dep_name = dependency
+            .and_then(|d| d.package.as_ref())
+            .unwrap_or(&dependency_key);
+
+        // Handle path dependencies
+        if let Some(dep_path) = dependency.and_then(|d| d.path.as_ref()) {
+            return Package::new_path_dependency(
+                &this_npm_package.package_path().join(dep_path),
+                dependency_key,
+                npm::wesl_name(dep_name),
+            );
+        }
+
+        let dep_dir = this_npm_package
+            .dep_by_name(dep_name)
+            .with_context(|| format!("dependency '{dep_name}' not found in package.json"))?;
+        let dep_pkg = npm_metadata
+            .package(dep_dir)
+            .context("invalid dependency")?;
+        Package::from_npm_package(dep_pkg, Some(dependency_key))
+    }
+
+    fn new_path_dependency(
+        dep_path: &Path,
+        dependency_key: String,
+        dep_name: String,
+    ) -> Result<Self> {
+        let (dep_wesl_toml, dep_has_wesl_toml_file) = load_wesl_toml(dep_path.join("wesl.toml"))?;
+        let root = dep_path.join(&dep_wesl_toml.package.root);
+
+        Ok(Package {
+            local_name: dependency_key,
+            package_name: dep_name,
+            version: version::path_dependency_version(dep_path, &root)?,
+            dir: dep_path.to_path_buf(),
+            root,
+            wesl_toml: dep_wesl_toml,
+            has_wesl_toml_file: dep_has_wesl_toml_file,
+        })
+    }
 }
 
 fn name_from_path(path: &Path) -> Result<String> {
@@ -1,8 +1,13 @@
 use clap::Parser;
+use std::process::ExitCode;
 
-fn main() -> anyhow::Result<()> {
+fn main() -> ExitCode {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
-    wesldoc::Args::parse().run()?;
-
-    Ok(())
+    match wesldoc::Args::parse().run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            ExitCode::from(wesldoc::exit_code(&err))
+        }
+    }
 }
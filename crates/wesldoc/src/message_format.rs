@@ -0,0 +1,253 @@
+use crate::CompileFailure;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use wesldoc_compiler::{CompileOutput, Diagnostic, Severity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum MessageFormat {
+    /// Human readable reports on stderr.
+    #[default]
+    Human,
+    /// One JSON object per diagnostic on stdout.
+    Json,
+    /// A single SARIF 2.1.0 log on stdout, written at the end of the run.
+    Sarif,
+}
+
+/// Emits diagnostics and compile failures in the selected message format.
+pub struct MessageSink {
+    format: MessageFormat,
+    messages: Vec<Message>,
+    /// The errors in the human readable format.
+    errors: Vec<String>,
+}
+
+/// A diagnostic with its location resolved to a file.
+#[derive(Debug, Serialize)]
+struct Message {
+    severity: &'static str,
+    rule: String,
+    message: String,
+    package: String,
+    module: String,
+    file: Option<PathBuf>,
+    range: Option<LineColumnRange>,
+}
+
+/// 1-based line and column range, the end is exclusive. Columns are counted in characters.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct LineColumnRange {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl MessageSink {
+    pub fn new(format: MessageFormat) -> Self {
+        Self {
+            format,
+            messages: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The errors and compile failures emitted so far, as they are shown to humans. They are
+    /// collected in all formats.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Emits the diagnostics of a compiled package. `root` is the directory of the package's
+    /// WESL sources.
+    pub fn diagnostics(&mut self, package: &str, root: &Path, output: &CompileOutput) {
+        for diagnostic in &output.diagnostics {
+            let rendered = format!("{:?}", diagnostic.to_report(&output.docs));
+            match self.format {
+                MessageFormat::Human => eprintln!("{rendered}"),
+                MessageFormat::Json | MessageFormat::Sarif => {
+                    let source = find_source(output, &diagnostic.module_path);
+                    self.emit(Message {
+                        severity: severity_str(diagnostic.severity),
+                        rule: diagnostic.lint.name().to_string(),
+                        message: diagnostic.message.clone(),
+                        package: package.to_string(),
+                        module: diagnostic.module_path.join("::"),
+                        file: module_file(root, diagnostic),
+                        range: diagnostic
+                            .span
+                            .clone()
+                            .zip(source)
+                            .map(|(span, source)| line_column_range(source, span)),
+                    });
+                }
+            }
+            if diagnostic.is_error() {
+                self.errors.push(rendered);
+            }
+        }
+    }
+
+    pub fn compile_failures(&mut self, failures: &[CompileFailure]) {
+        if failures.is_empty() {
+            return;
+        }
+        self.errors
+            .extend(failures.iter().map(|failure| failure.to_string()));
+
+        match self.format {
+            MessageFormat::Human => {
+                eprintln!("{} module(s) failed to compile:", failures.len());
+                for failure in failures {
+                    eprintln!("\n{failure}");
+                }
+            }
+            MessageFormat::Json | MessageFormat::Sarif => {
+                for failure in failures {
+                    self.emit(Message {
+                        severity: severity_str(Severity::Error),
+                        rule: "compile_error".to_string(),
+                        message: failure.message.clone(),
+                        package: failure.package.clone(),
+                        module: failure.module.components.join("::"),
+                        file: Some(failure.file.clone()),
+                        range: None,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Writes messages that are only emitted at the end of the run.
+    pub fn finish(self) {
+        if self.format != MessageFormat::Sarif {
+            return;
+        }
+
+        let results = self
+            .messages
+            .iter()
+            .map(|message| {
+                let mut result = json!({
+                    "ruleId": message.rule,
+                    "level": message.severity,
+                    "message": { "text": message.message },
+                });
+                if let Some(file) = &message.file {
+                    let mut location = json!({
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": file_uri(file) },
+                        },
+                    });
+                    if let Some(range) = message.range {
+                        location["physicalLocation"]["region"] = json!({
+                            "startLine": range.start_line,
+                            "startColumn": range.start_column,
+                            "endLine": range.end_line,
+                            "endColumn": range.end_column,
+                        });
+                    }
+                    result["locations"] = json!([location]);
+                }
+                result
+            })
+            .collect::<Vec<_>>();
+
+        let mut rules = self
+            .messages
+            .iter()
+            .map(|message| message.rule.as_str())
+            .collect::<Vec<_>>();
+        rules.sort();
+        rules.dedup();
+
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "wesldoc",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules
+                            .iter()
+                            .map(|rule| json!({ "id": rule }))
+                            .collect::<Vec<_>>(),
+                    },
+                },
+                "results": results,
+            }],
+        });
+        println!("{log:#}");
+    }
+
+    fn emit(&mut self, message: Message) {
+        match self.format {
+            MessageFormat::Human => (),
+            MessageFormat::Json => match serde_json::to_string(&message) {
+                Ok(line) => println!("{line}"),
+                Err(err) => eprintln!("failed to serialize diagnostic: {err}"),
+            },
+            MessageFormat::Sarif => self.messages.push(message),
+        }
+    }
+}
+
+fn severity_str(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+fn find_source<'a>(output: &'a CompileOutput, module_path: &[String]) -> Option<&'a str> {
+    let mut module = &output.docs.root;
+    for name in module_path {
+        module = module.modules.iter().find(|m| m.name == *name)?;
+    }
+    module.source.as_deref()
+}
+
+fn module_file(root: &Path, diagnostic: &Diagnostic) -> Option<PathBuf> {
+    if diagnostic.module_path.is_empty() {
+        return None;
+    }
+    let path = root.join(diagnostic.module_path.join("/"));
+    ["wesl", "wgsl"]
+        .into_iter()
+        .map(|ext| path.with_extension(ext))
+        .find(|path| path.is_file())
+}
+
+fn file_uri(file: &Path) -> String {
+    let file = std::env::current_dir()
+        .ok()
+        .and_then(|dir| file.strip_prefix(dir).ok())
+        .unwrap_or(file);
+    file.to_string_lossy().replace('\\', "/")
+}
+
+fn line_column_range(source: &str, span: Range<usize>) -> LineColumnRange {
+    let (start_line, start_column) = line_column(source, span.start);
+    let (end_line, end_column) = line_column(source, span.end);
+    LineColumnRange {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    }
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = source.get(..offset).unwrap_or(source);
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
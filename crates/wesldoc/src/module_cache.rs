@@ -0,0 +1,91 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+};
+use wesl::{ModulePath, ResolveError, syntax::TranslationUnit};
+
+/// Sources and syntax trees of all modules read during a run, shared by the resolvers of all
+/// packages. Modules are keyed by the root of the package they belong to and their path in it, so
+/// a dependency imported by several packages is only read and parsed once.
+#[derive(Default)]
+pub struct ModuleCache {
+    modules: Mutex<HashMap<(PathBuf, ModulePath), Arc<CachedModule>>>,
+    /// Read and parse modules on every request instead, as a baseline for benchmarks.
+    #[cfg(feature = "test-hooks")]
+    disabled: bool,
+}
+
+pub struct CachedModule {
+    pub source: String,
+    /// Parsed on first use. Errors are kept as messages, callers that need the error itself parse
+    /// the source again.
+    syntax: OnceLock<Result<TranslationUnit, String>>,
+}
+
+impl CachedModule {
+    pub fn syntax(&self) -> Result<&TranslationUnit, &str> {
+        self.syntax
+            .get_or_init(|| parse(&self.source).map_err(|err| format!("{err}")))
+            .as_ref()
+            .map_err(String::as_str)
+    }
+}
+
+impl ModuleCache {
+    #[cfg(feature = "test-hooks")]
+    pub fn disabled() -> Self {
+        Self {
+            disabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the cached module, or reads it with `read`. The lock is not held while reading, so
+    /// a module that is requested concurrently may be read twice, but only one copy is kept.
+    pub fn module(
+        &self,
+        root: &Path,
+        path: &ModulePath,
+        read: impl FnOnce() -> Result<String, ResolveError>,
+    ) -> Result<Arc<CachedModule>, ResolveError> {
+        let key = (root.to_path_buf(), path.clone());
+        if let Some(module) = self.modules.lock().unwrap().get(&key) {
+            return Ok(Arc::clone(module));
+        }
+
+        let module = Arc::new(CachedModule {
+            source: read()?,
+            syntax: OnceLock::new(),
+        });
+        #[cfg(feature = "test-hooks")]
+        if self.disabled {
+            return Ok(module);
+        }
+        Ok(Arc::clone(
+            self.modules.lock().unwrap().entry(key).or_insert(module),
+        ))
+    }
+}
+
+/// Parses a module. All modules of a run are parsed through here, so the parses can be counted.
+pub fn parse(source: &str) -> Result<TranslationUnit, <TranslationUnit as FromStr>::Err> {
+    #[cfg(feature = "test-hooks")]
+    parse_counter::PARSED_MODULES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    source.parse()
+}
+
+#[cfg(feature = "test-hooks")]
+pub mod parse_counter {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Modules parsed by this process.
+    pub(super) static PARSED_MODULES: AtomicUsize = AtomicUsize::new(0);
+
+    /// The number of modules parsed by this process so far, to check that each module is parsed
+    /// once per run.
+    pub fn parsed_modules() -> usize {
+        PARSED_MODULES.load(Ordering::Relaxed)
+    }
+}
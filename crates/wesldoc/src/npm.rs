@@ -0,0 +1,196 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use wesldoc_ast::Version;
+
+/// Packages installed in a local `node_modules` tree, starting from the `package.json` in the
+/// project directory.
+pub struct NpmMetadata {
+    packages: HashMap<PathBuf, NpmPackage>,
+    root: PathBuf,
+}
+
+impl NpmMetadata {
+    pub fn resolve(base_path: impl Into<PathBuf>) -> Result<Self> {
+        let base_path = base_path.into();
+        let project_dir = canonical(&base_path)?;
+
+        let mut packages = HashMap::new();
+        let mut stack = vec![project_dir.clone()];
+        while let Some(dir) = stack.pop() {
+            if packages.contains_key(&dir) {
+                continue;
+            }
+
+            let package_json = PackageJson::load(&dir)?;
+            let mut deps = BTreeMap::new();
+            for dep_name in package_json.dependencies.keys() {
+                let dep_dir =
+                    find_in_node_modules(&dir, &project_dir, dep_name)?.with_context(|| {
+                        format!(
+                            "dependency '{dep_name}' of '{}' is not installed in node_modules, \
+                             run `npm install` first",
+                            package_json.name
+                        )
+                    })?;
+                stack.push(dep_dir.clone());
+                deps.insert(dep_name.clone(), dep_dir);
+            }
+
+            let version = Version::parse(&package_json.version).with_context(|| {
+                format!(
+                    "invalid version '{}' of npm package '{}'",
+                    package_json.version, package_json.name
+                )
+            })?;
+            packages.insert(
+                dir.clone(),
+                NpmPackage {
+                    name: package_json.name,
+                    version,
+                    dir,
+                    deps,
+                },
+            );
+        }
+
+        Ok(Self {
+            packages,
+            root: project_dir,
+        })
+    }
+
+    pub fn root(&self) -> &NpmPackage {
+        &self.packages[&self.root]
+    }
+
+    pub fn package(&self, dir: &Path) -> Option<&NpmPackage> {
+        self.packages.get(dir)
+    }
+
+    /// Iterates over the root package and its dependencies up to `max_depth`. Packages that are
+    /// installed once but required by several packages are only returned once.
+    pub fn iter_packages(&self, max_depth: usize) -> Vec<&NpmPackage> {
+        let mut packages = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(self.root(), 0)];
+        while let Some((package, depth)) = stack.pop() {
+            if !visited.insert(&package.dir) {
+                continue;
+            }
+            if depth < max_depth {
+                stack.extend(
+                    package
+                        .deps
+                        .values()
+                        .rev()
+                        .filter_map(|dir| self.packages.get(dir))
+                        .map(|dep| (dep, depth + 1)),
+                );
+            }
+            packages.push(package);
+        }
+        packages
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NpmPackage {
+    name: String,
+    version: Version,
+    dir: PathBuf,
+    /// Installed directories of the dependencies, keyed by their npm name.
+    deps: BTreeMap<String, PathBuf>,
+}
+
+impl NpmPackage {
+    /// The npm name turned into a WESL package identifier, see [`wesl_name`].
+    pub fn name(&self) -> String {
+        wesl_name(&self.name)
+    }
+
+    pub fn version(&self) -> Version {
+        self.version.clone()
+    }
+
+    pub fn package_path(&self) -> PathBuf {
+        self.dir.clone()
+    }
+
+    pub fn dep(&self, npm_name: &str) -> Option<&PathBuf> {
+        self.deps.get(npm_name)
+    }
+
+    /// Finds a dependency by its npm name or its WESL package identifier.
+    pub fn dep_by_name(&self, name: &str) -> Option<&PathBuf> {
+        self.dep(name).or_else(|| {
+            self.deps
+                .iter()
+                .find(|(npm_name, _)| wesl_name(npm_name) == name)
+                .map(|(_, dir)| dir)
+        })
+    }
+}
+
+/// Turns an npm name into a WESL package identifier by replacing all characters that are not
+/// allowed with `_`, e.g. `@scope/my-lib` becomes `scope_my_lib`.
+pub fn wesl_name(npm_name: &str) -> String {
+    npm_name
+        .trim_start_matches('@')
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    name: String,
+    version: String,
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+}
+
+impl PackageJson {
+    fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join("package.json");
+        let content =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_slice(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
+
+/// Looks up a package the way node does: in the `node_modules` directory of the requiring
+/// package first and then in the `node_modules` directories of its ancestors, which is where
+/// hoisted packages are installed. The lookup stops at the project directory.
+fn find_in_node_modules(from: &Path, project_dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+    if name
+        .split('/')
+        .any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        bail!("invalid npm package name '{name}'");
+    }
+
+    for dir in from
+        .ancestors()
+        .take_while(|dir| dir.starts_with(project_dir))
+    {
+        let candidate = dir.join("node_modules").join(name);
+        if candidate.join("package.json").is_file() {
+            return Ok(Some(canonical(&candidate)?));
+        }
+    }
+    Ok(None)
+}
+
+fn canonical(dir: &Path) -> Result<PathBuf> {
+    dir.canonicalize()
+        .with_context(|| format!("package directory {} not found", dir.display()))
+}
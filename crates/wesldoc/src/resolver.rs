@@ -1,93 +1,202 @@
 use crate::{
     Package,
     cargo::{CargoMetadata, CargoPackage},
+    module_cache::{self, CachedModule, ModuleCache},
+    npm::{NpmMetadata, NpmPackage},
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
-use std::{borrow::Cow, cell::RefCell, collections::HashMap, rc::Rc};
 use wesl::{
     FileResolver, ModulePath, ResolveError, Resolver,
     syntax::{ImportStatement, PathOrigin, TranslationUnit},
 };
+use wesldoc_ast::IndexMap;
+use wesldoc_compiler::ParsedSource;
 
+/// Resolves the modules of a package and its dependencies. It is shared by all modules of the
+/// package, which `wesl` compiles one after another. Sources and syntax trees come from the
+/// [`ModuleCache`] of the run, so each file is read and parsed once.
 pub struct DocsResolver {
-    this: FileResolver,
+    this: (PathBuf, FileResolver),
     dependencies: Dependencies,
+    modules: Arc<ModuleCache>,
 
-    root_file_imports: RefCell<Option<Vec<ImportStatement>>>,
+    /// Every module `wesl` resolved, keyed by path, so the imports and syntax trees of compiled
+    /// modules can be looked up afterwards.
+    resolved: Mutex<IndexMap<ModulePath, Arc<CachedModule>>>,
 }
 
 enum Dependencies {
     Explicit {
-        dependencies: HashMap<String, (Package, FileResolver)>,
+        dependencies: HashMap<String, (Package, PathBuf, FileResolver)>,
     },
     Auto {
-        dependencies: RefCell<HashMap<String, (Package, FileResolver)>>,
-        cargo_metadata: Rc<CargoMetadata>,
+        dependencies: Mutex<HashMap<String, (Package, PathBuf, Arc<FileResolver>)>>,
+        source: AutoSource,
+    },
+}
+
+/// Where dependencies are looked up with `dependencies = "auto"`.
+enum AutoSource {
+    Cargo {
+        cargo_metadata: Arc<CargoMetadata>,
         this_cargo_package: Box<CargoPackage>,
     },
+    Npm {
+        npm_metadata: Arc<NpmMetadata>,
+        this_npm_package: Box<NpmPackage>,
+    },
+}
+
+impl AutoSource {
+    fn dependency(&self, dependency_key: &str) -> anyhow::Result<Package> {
+        match self {
+            AutoSource::Cargo {
+                cargo_metadata,
+                this_cargo_package,
+            } => Package::new_dependency(this_cargo_package, dependency_key, None, cargo_metadata),
+            AutoSource::Npm {
+                npm_metadata,
+                this_npm_package,
+            } => Package::new_npm_dependency(this_npm_package, dependency_key, None, npm_metadata),
+        }
+    }
 }
 
 impl DocsResolver {
-    pub fn new_explicit(this: &Package, dependencies: impl IntoIterator<Item = Package>) -> Self {
+    pub fn new_explicit(
+        this: &Package,
+        dependencies: impl IntoIterator<Item = Package>,
+        modules: Arc<ModuleCache>,
+    ) -> Self {
         Self {
-            this: FileResolver::new(&this.root),
+            this: (cache_root(&this.root), FileResolver::new(&this.root)),
             dependencies: Dependencies::Explicit {
                 dependencies: dependencies
                     .into_iter()
                     .map(|dep| {
+                        let root = cache_root(&dep.root);
                         let resolver = FileResolver::new(&dep.root);
-                        (dep.local_name.clone(), (dep, resolver))
+                        (dep.local_name.clone(), (dep, root, resolver))
                     })
                     .collect(),
             },
+            modules,
 
-            root_file_imports: RefCell::new(None),
+            resolved: Mutex::new(IndexMap::new()),
         }
     }
 
     pub fn new_auto(
         this: &Package,
-        cargo_metadata: Rc<CargoMetadata>,
+        cargo_metadata: Arc<CargoMetadata>,
         cargo_package: CargoPackage,
+        modules: Arc<ModuleCache>,
     ) -> Self {
-        Self {
-            this: FileResolver::new(&this.root),
-            dependencies: Dependencies::Auto {
-                dependencies: RefCell::new(HashMap::new()),
+        Self::new_auto_from(
+            this,
+            AutoSource::Cargo {
                 cargo_metadata,
                 this_cargo_package: Box::new(cargo_package),
             },
+            modules,
+        )
+    }
 
-            root_file_imports: RefCell::new(None),
+    pub fn new_auto_npm(
+        this: &Package,
+        npm_metadata: Arc<NpmMetadata>,
+        npm_package: NpmPackage,
+        modules: Arc<ModuleCache>,
+    ) -> Self {
+        Self::new_auto_from(
+            this,
+            AutoSource::Npm {
+                npm_metadata,
+                this_npm_package: Box::new(npm_package),
+            },
+            modules,
+        )
+    }
+
+    fn new_auto_from(this: &Package, source: AutoSource, modules: Arc<ModuleCache>) -> Self {
+        Self {
+            this: (cache_root(&this.root), FileResolver::new(&this.root)),
+            dependencies: Dependencies::Auto {
+                dependencies: Mutex::new(HashMap::new()),
+                source,
+            },
+            modules,
+
+            resolved: Mutex::new(IndexMap::new()),
         }
     }
 
     pub fn resolved_dependencies(&self) -> Vec<Package> {
         match &self.dependencies {
-            Dependencies::Explicit { dependencies } => {
-                dependencies.values().map(|(pkg, _)| pkg.clone()).collect()
-            }
+            Dependencies::Explicit { dependencies } => dependencies
+                .values()
+                .map(|(pkg, _, _)| pkg.clone())
+                .collect(),
             Dependencies::Auto { dependencies, .. } => dependencies
-                .borrow()
+                .lock()
+                .unwrap()
                 .values()
-                .map(|(pkg, _)| pkg.clone())
+                .map(|(pkg, _, _)| pkg.clone())
                 .collect(),
         }
     }
 
-    pub fn take_root_file_imports(&self) -> Vec<ImportStatement> {
-        self.root_file_imports
-            .borrow_mut()
-            .take()
+    /// The imports of a module that was compiled with this resolver.
+    pub fn root_file_imports(&self, path: &ModulePath) -> Vec<ImportStatement> {
+        self.resolved
+            .lock()
+            .unwrap()
+            .get(path)
+            .and_then(|module| Some(module.syntax().ok()?.imports.clone()))
             .unwrap_or_default()
     }
 
+    /// The sources and syntax trees of all modules `wesl` resolved, so they are not parsed again
+    /// while documenting.
+    pub fn resolved_sources(&self) -> IndexMap<ModulePath, ParsedSource> {
+        self.resolved
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(path, module)| {
+                let parsed = ParsedSource {
+                    source: module.source.clone(),
+                    syntax: module.syntax().ok()?.clone(),
+                };
+                Some((path.clone(), parsed))
+            })
+            .collect()
+    }
+
+    /// Reads a module of the package or of a dependency through the shared [`ModuleCache`].
+    pub fn module(&self, path: &ModulePath) -> Result<Arc<CachedModule>, ResolveError> {
+        self.resolve(path, |root, resolver, path| {
+            self.modules.module(root, path, || {
+                resolver.resolve_source(path).map(|source| source.into())
+            })
+        })
+    }
+
     fn resolve<T>(
         &self,
         path: &ModulePath,
-        f: impl FnOnce(&FileResolver, &ModulePath) -> Result<T, ResolveError>,
+        f: impl FnOnce(&Path, &FileResolver, &ModulePath) -> Result<T, ResolveError>,
     ) -> Result<T, ResolveError> {
         match &path.origin {
-            PathOrigin::Absolute | PathOrigin::Relative(_) => Ok(f(&self.this, path)?),
+            PathOrigin::Absolute | PathOrigin::Relative(_) => {
+                let (root, resolver) = &self.this;
+                Ok(f(root, resolver, path)?)
+            }
             PathOrigin::Package(package) => {
                 // Rebase the path to be absolute
                 let path_absolute = ModulePath {
@@ -98,40 +207,39 @@ impl DocsResolver {
                 // Look up the resolver for the package
                 match &self.dependencies {
                     Dependencies::Explicit { dependencies } => {
-                        let (_, resolver) = dependencies.get(package).ok_or_else(|| {
+                        let (_, root, resolver) = dependencies.get(package).ok_or_else(|| {
                             ResolveError::ModuleNotFound(
                                 path.clone(),
                                 "package not found".to_string(),
                             )
                         })?;
-                        f(resolver, &path_absolute)
+                        f(root, resolver, &path_absolute)
                     }
                     Dependencies::Auto {
                         dependencies,
-                        cargo_metadata,
-                        this_cargo_package,
+                        source,
                     } => {
-                        let mut dependencies = dependencies.borrow_mut();
-                        if let Some((_, resolver)) = dependencies.get(package) {
-                            return f(resolver, &path_absolute);
-                        }
-
-                        // Dependency not used yet, try to find it
-                        let dep = Package::new_dependency(
-                            this_cargo_package,
-                            package,
-                            None,
-                            cargo_metadata,
-                        )
-                        .map_err(|err| {
-                            ResolveError::ModuleNotFound(path.clone(), err.to_string())
-                        })?;
-                        let resolver = FileResolver::new(&dep.root);
-
-                        let res = f(&resolver, &path_absolute);
-                        dependencies.insert(dep.local_name.clone(), (dep, resolver));
-
-                        Ok(res?)
+                        // The lock is not held while looking up the dependency
+                        let (root, resolver) = {
+                            let mut dependencies = dependencies.lock().unwrap();
+                            match dependencies.get(package) {
+                                Some((_, root, resolver)) => (root.clone(), Arc::clone(resolver)),
+                                None => {
+                                    // Dependency not used yet, try to find it
+                                    let dep = source.dependency(package).map_err(|err| {
+                                        ResolveError::ModuleNotFound(path.clone(), err.to_string())
+                                    })?;
+                                    let root = cache_root(&dep.root);
+                                    let resolver = Arc::new(FileResolver::new(&dep.root));
+                                    dependencies.insert(
+                                        dep.local_name.clone(),
+                                        (dep, root.clone(), Arc::clone(&resolver)),
+                                    );
+                                    (root, resolver)
+                                }
+                            }
+                        };
+                        f(&root, &resolver, &path_absolute)
                     }
                 }
             }
@@ -141,31 +249,38 @@ impl DocsResolver {
 
 impl Resolver for DocsResolver {
     fn resolve_source<'a>(&'a self, path: &ModulePath) -> Result<Cow<'a, str>, ResolveError> {
-        self.resolve(path, |resolver, path| {
-            resolver
-                .resolve_source(path)
-                .map(|source| Cow::Owned(source.into()))
-        })
+        Ok(Cow::Owned(self.module(path)?.source.clone()))
     }
 
     fn resolve_module(&self, path: &ModulePath) -> Result<TranslationUnit, ResolveError> {
-        let source = self.resolve_source(path)?;
-        let wesl = source.parse::<TranslationUnit>().map_err(|e| {
-            wesl::Diagnostic::from(e)
-                .with_module_path(path.clone(), self.display_name(path))
-                .with_source(source.to_string())
-        })?;
-
-        let mut root_file_imports = self.root_file_imports.borrow_mut();
-        if root_file_imports.is_none() {
-            *root_file_imports = Some(wesl.imports.clone());
-        }
+        let module = self.module(path)?;
+        let wesl = match module.syntax() {
+            Ok(syntax) => syntax.clone(),
+            // Parse again to report the error with its location
+            Err(_) => module_cache::parse(&module.source).map_err(|e| {
+                wesl::Diagnostic::from(e)
+                    .with_module_path(path.clone(), self.display_name(path))
+                    .with_source(module.source.clone())
+            })?,
+        };
+
+        self.resolved
+            .lock()
+            .unwrap()
+            .entry(path.clone())
+            .or_insert(module);
 
         Ok(wesl)
     }
 
     fn display_name(&self, path: &ModulePath) -> Option<String> {
-        self.resolve(path, |resolver, path| Ok(resolver.display_name(path)))
+        self.resolve(path, |_, resolver, path| Ok(resolver.display_name(path)))
             .ok()?
     }
 }
+
+/// The root of a package as used in [`ModuleCache`] keys. Packages are often reached through
+/// different relative paths, e.g. `app/../geometry` and `geometry`.
+fn cache_root(root: &Path) -> PathBuf {
+    root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+}
@@ -0,0 +1,543 @@
+use crate::{Build, Package, PackageArgs};
+use anyhow::{Result, anyhow};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Condvar, Mutex,
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use tiny_http::{Header, Request, Response, Server};
+
+/// How long to wait for more changes before rebuilding, e.g. when several files are saved.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Browsers subscribe to this path to be told when a build finished.
+const EVENTS_PATH: &str = "/-/livereload";
+
+/// Injected into every served page, reloads it when a build finished.
+const LIVE_RELOAD_SCRIPT: &str =
+    r#"<script>new EventSource("/-/livereload").onmessage = () => location.reload();</script>"#;
+
+/// A running `wesldoc serve`. The docs are built and rebuilt on one thread, connections are
+/// accepted on another. Both are stopped when the server is shut down or dropped.
+pub struct DocServer {
+    address: String,
+    http: Arc<Server>,
+    live_reload: Arc<LiveReload>,
+    messages: Sender<Message>,
+    builder: Option<JoinHandle<Result<()>>>,
+    listener: Option<JoinHandle<()>>,
+}
+
+enum Message {
+    Changed(notify::Result<Event>),
+    Stop,
+}
+
+impl DocServer {
+    /// Listens on `address` and starts building the docs into `output`. Port `0` picks a free
+    /// port, see [`DocServer::address`].
+    pub(crate) fn start(args: PackageArgs, output: PathBuf, address: &str) -> Result<Self> {
+        let http = Arc::new(
+            Server::http(address).map_err(|err| anyhow!("failed to listen on {address}: {err}"))?,
+        );
+        let address = http
+            .server_addr()
+            .to_ip()
+            .map_or_else(|| address.to_string(), |address| address.to_string());
+        let live_reload = Arc::new(LiveReload::default());
+
+        let listener = {
+            let http = Arc::clone(&http);
+            let live_reload = Arc::clone(&live_reload);
+            let output = output.clone();
+            thread::spawn(move || {
+                // Waits for the open connections once no more are accepted
+                thread::scope(|scope| {
+                    for request in http.incoming_requests() {
+                        scope.spawn(|| handle(request, &output, &live_reload));
+                    }
+                });
+            })
+        };
+
+        let (messages, receiver) = mpsc::channel();
+        let builder = {
+            let messages = messages.clone();
+            let live_reload = Arc::clone(&live_reload);
+            let address = address.clone();
+            thread::spawn(move || {
+                build_loop(args, &output, &address, &live_reload, messages, &receiver)
+            })
+        };
+
+        Ok(Self {
+            address,
+            http,
+            live_reload,
+            messages,
+            builder: Some(builder),
+            listener: Some(listener),
+        })
+    }
+
+    /// The address the docs are served on.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Serves until the builder fails, which only happens if files can't be watched anymore.
+    pub fn wait(mut self) -> Result<()> {
+        join(self.builder.take())
+    }
+
+    /// Waits for the current build and stops the server. Open connections are closed.
+    pub fn shutdown(mut self) -> Result<()> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        let _ = self.messages.send(Message::Stop);
+        let result = join(self.builder.take());
+        self.live_reload.close();
+        self.http.unblock();
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+        result
+    }
+}
+
+impl Drop for DocServer {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+fn join(builder: Option<JoinHandle<Result<()>>>) -> Result<()> {
+    match builder.map(JoinHandle::join) {
+        Some(Ok(result)) => result,
+        Some(Err(_)) => Err(anyhow!("the builder panicked")),
+        None => Ok(()),
+    }
+}
+
+/// Builds the docs and rebuilds the affected packages whenever their sources or manifests
+/// change, until it is told to stop. Failed builds are shown as an overlay on every page.
+fn build_loop(
+    mut args: PackageArgs,
+    output: &Path,
+    address: &str,
+    live_reload: &LiveReload,
+    messages: Sender<Message>,
+    receiver: &Receiver<Message>,
+) -> Result<()> {
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = messages.send(Message::Changed(event));
+    })?;
+    let mut watched = HashSet::new();
+    let mut sources = Vec::new();
+    let mut dependencies = HashMap::new();
+    let mut announced = false;
+    loop {
+        let build = args.build(Some(output));
+        if let Err(err) = &build.result {
+            eprintln!("error: {err:#}");
+        }
+        let error = build_error(&build);
+        let failed = error.is_some();
+        live_reload.finish_build(error);
+        if !announced {
+            args.status(format_args!("Serving docs at http://{address}"));
+            announced = true;
+        }
+
+        // Watch the sources and manifests of all packages. A failed run may not know its
+        // packages, so the ones of the previous run are kept.
+        if !build.packages.is_empty() {
+            sources = build
+                .packages
+                .iter()
+                .filter_map(PackageSources::new)
+                .collect();
+        }
+        dependencies.extend(build.dependencies);
+        let manifest_dir = args
+            .manifest_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .unwrap_or(&args.path);
+        let watches = sources
+            .iter()
+            .flat_map(|sources| {
+                [
+                    (sources.root.clone(), RecursiveMode::Recursive),
+                    (sources.dir.clone(), RecursiveMode::NonRecursive),
+                ]
+            })
+            .chain([(manifest_dir.to_path_buf(), RecursiveMode::NonRecursive)]);
+        for (path, mode) in watches {
+            if path.exists() && watched.insert((path.clone(), mode)) {
+                watcher.watch(&path, mode)?;
+            }
+        }
+
+        // A failed run is repeated in full to show all remaining errors
+        let Some(changed) = wait_for_changes(receiver)? else {
+            return Ok(());
+        };
+        args.only = match failed {
+            true => None,
+            false => affected_packages(&sources, &dependencies, &changed),
+        };
+    }
+}
+
+/// Blocks until sources or manifests were created, changed or removed and returns their paths,
+/// or `None` once the server is stopped. Reading files also causes events, e.g. while building,
+/// so only writes are considered.
+fn wait_for_changes(receiver: &Receiver<Message>) -> Result<Option<Vec<PathBuf>>> {
+    let mut changed = Vec::new();
+    let mut message = receiver.recv()?;
+    loop {
+        match message {
+            Message::Changed(Ok(event))
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) =>
+            {
+                changed.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .filter(|path| is_source(path) || is_manifest(path)),
+                );
+            }
+            Message::Changed(Ok(_)) => {}
+            Message::Changed(Err(err)) => eprintln!("failed to watch files: {err}"),
+            Message::Stop => return Ok(None),
+        }
+
+        // Collect changes until no more arrive
+        message = match changed.is_empty() {
+            true => receiver.recv()?,
+            false => match receiver.recv_timeout(DEBOUNCE) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(err) => return Err(err.into()),
+            },
+        };
+    }
+
+    changed.sort();
+    changed.dedup();
+    Ok(Some(changed))
+}
+
+/// Where a package's files are found on disk, canonicalized to match the paths of file events.
+struct PackageSources {
+    /// The directory of the package as it is selected with [`PackageArgs::only`].
+    package_dir: PathBuf,
+    dir: PathBuf,
+    root: PathBuf,
+}
+
+impl PackageSources {
+    fn new(package: &Package) -> Option<Self> {
+        Some(Self {
+            package_dir: package.dir.clone(),
+            dir: package.dir.canonicalize().ok()?,
+            root: package.root.canonicalize().ok()?,
+        })
+    }
+}
+
+/// The packages to rebuild after `changed` files changed, or `None` to rebuild all of them.
+/// Packages that depend on a rebuilt package, directly or through other packages, are rebuilt
+/// too. Manifests can change which packages belong to the run, so they cause a full rebuild.
+fn affected_packages(
+    sources: &[PackageSources],
+    dependencies: &HashMap<PathBuf, Vec<PathBuf>>,
+    changed: &[PathBuf],
+) -> Option<Vec<PathBuf>> {
+    let mut affected = Vec::new();
+    for path in changed {
+        if is_manifest(path) {
+            return None;
+        }
+        let package = sources
+            .iter()
+            .find(|sources| path.starts_with(&sources.root))?;
+        if !affected.contains(&package.package_dir) {
+            affected.push(package.package_dir.clone());
+        }
+    }
+
+    // Add the packages that depend on affected ones until none are left. The dependencies of
+    // packages that were never compiled, e.g. because their docs were up to date, are unknown,
+    // so they are rebuilt to be safe.
+    loop {
+        let dependents = sources
+            .iter()
+            .map(|sources| &sources.package_dir)
+            .filter(|dir| {
+                !affected.contains(dir)
+                    && dependencies.get(*dir).is_none_or(|dependencies| {
+                        dependencies.iter().any(|dep| affected.contains(dep))
+                    })
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if dependents.is_empty() {
+            break;
+        }
+        affected.extend(dependents);
+    }
+    Some(affected)
+}
+
+fn is_source(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "wesl" || ext == "wgsl")
+}
+
+fn is_manifest(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "wesl.toml" || name == "Cargo.toml")
+}
+
+/// The errors of a build as plain text, or `None` if it succeeded.
+fn build_error(build: &Build) -> Option<String> {
+    let mut errors = build
+        .errors
+        .iter()
+        .map(|error| strip_ansi(error))
+        .collect::<Vec<_>>();
+    if let Err(err) = &build.result {
+        errors.push(format!("error: {err:#}"));
+    }
+    (!errors.is_empty()).then(|| errors.join("\n\n"))
+}
+
+/// Removes the color codes of terminal output.
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to the final byte of the escape sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// The outcome of the last build, shared with the connections of the browsers.
+#[derive(Default)]
+struct LiveReload {
+    state: Mutex<BuildState>,
+    finished: Condvar,
+}
+
+#[derive(Default)]
+struct BuildState {
+    /// Counts the finished builds.
+    generation: u64,
+    error: Option<String>,
+    /// Set when the server shuts down, so the event streams end.
+    closed: bool,
+}
+
+impl LiveReload {
+    fn finish_build(&self, error: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.error = error;
+        self.finished.notify_all();
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.finished.notify_all();
+    }
+
+    fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    fn error(&self) -> Option<String> {
+        self.state.lock().unwrap().error.clone()
+    }
+
+    /// Waits until a build after `generation` finished or the timeout elapsed. Returns the
+    /// current generation, or `None` once the server shuts down.
+    fn wait(&self, generation: u64, timeout: Duration) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        let (state, _) = self
+            .finished
+            .wait_timeout_while(state, timeout, |state| {
+                state.generation == generation && !state.closed
+            })
+            .unwrap();
+        (!state.closed).then_some(state.generation)
+    }
+}
+
+fn handle(request: Request, output: &Path, live_reload: &LiveReload) {
+    let url = request
+        .url()
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    if url == EVENTS_PATH {
+        // Fails once the browser navigated away
+        let _ = send_events(request, live_reload);
+        return;
+    }
+
+    let response = match file_path(output, &url) {
+        Some(path) if path.is_dir() && !url.ends_with('/') => Response::from_data(Vec::new())
+            .with_status_code(301)
+            .with_header(header("Location", &format!("{url}/"))),
+        Some(path) => {
+            let path = match path.is_dir() {
+                true => path.join("index.html"),
+                false => path,
+            };
+            match fs::read(&path) {
+                Ok(content) if is_html(&path) => {
+                    html_response(&String::from_utf8_lossy(&content), 200, live_reload.error())
+                }
+                Ok(content) => Response::from_data(content)
+                    .with_header(header("Content-Type", content_type(&path))),
+                Err(_) => not_found(live_reload),
+            }
+        }
+        None => not_found(live_reload),
+    };
+    let _ = request.respond(response);
+}
+
+/// Streams server-sent events to the browser, one `reload` event per finished build, until the
+/// server shuts down. Comments are sent in between to notice closed connections.
+fn send_events(request: Request, live_reload: &LiveReload) -> io::Result<()> {
+    let mut generation = live_reload.generation();
+    let mut writer = request.into_writer();
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n",
+    )?;
+    writer.flush()?;
+    while let Some(current) = live_reload.wait(generation, Duration::from_secs(15)) {
+        let event: &[u8] = match current == generation {
+            true => b": keep-alive\n\n",
+            false => b"data: reload\n\n",
+        };
+        generation = current;
+        writer.write_all(event)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Maps a request path to a path in the output directory. Paths that leave it are rejected.
+fn file_path(output: &Path, url: &str) -> Option<PathBuf> {
+    let mut path = output.to_path_buf();
+    for segment in url.split('/').filter(|segment| !segment.is_empty()) {
+        // Decoded after splitting, so an encoded `/` can't add segments
+        let segment = percent_decode(segment)?;
+        if segment == "." || segment == ".." || segment.contains(['/', '\\']) {
+            return None;
+        }
+        path.push(segment);
+    }
+    Some(path)
+}
+
+/// Decodes the `%XX` escapes of a URL path segment. Returns `None` for invalid escapes or if
+/// the result is not UTF-8.
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn not_found(live_reload: &LiveReload) -> Response<io::Cursor<Vec<u8>>> {
+    html_response(
+        "<!DOCTYPE html><html><head><title>Not found</title></head><body><h1>Not found</h1></body></html>",
+        404,
+        live_reload.error(),
+    )
+}
+
+/// Adds the live reload script and, if the last build failed, an overlay with its errors.
+fn html_response(html: &str, status: u16, error: Option<String>) -> Response<io::Cursor<Vec<u8>>> {
+    let mut injected = LIVE_RELOAD_SCRIPT.to_string();
+    if let Some(error) = error {
+        injected.push_str(&format!(
+            r#"<div id="wesldoc-serve-error" style="position: fixed; inset: 0; z-index: 1000; overflow: auto; margin: 0; padding: 2em; background: rgba(24, 24, 24, 0.96); color: #f0f0f0;"><h2 style="color: #ff6b6b; font-family: sans-serif;">Build failed</h2><pre style="white-space: pre-wrap; font-size: 14px;">{}</pre></div>"#,
+            escape_html(&error)
+        ));
+    }
+    let html = match html.rfind("</body>") {
+        Some(idx) => format!("{}{injected}{}", &html[..idx], &html[idx..]),
+        None => format!("{html}{injected}"),
+    };
+
+    Response::from_data(html)
+        .with_status_code(status)
+        .with_header(header("Content-Type", "text/html; charset=utf-8"))
+        .with_header(header("Cache-Control", "no-cache"))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn is_html(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "html")
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        Some("woff2") => "font/woff2",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("valid header")
+}
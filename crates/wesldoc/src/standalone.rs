@@ -0,0 +1,113 @@
+use crate::Package;
+use anyhow::{Context, Result, bail};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// A package that is only defined by its `wesl.toml`, without a package manager.
+pub struct StandalonePackage {
+    pub package: Package,
+    /// Direct dependencies of the package.
+    pub dependencies: Vec<Package>,
+}
+
+/// Resolves the standalone package in `dir` and its path dependencies up to `max_depth`.
+/// Packages that are reachable through several paths are only returned once. The package in `dir`
+/// must set a version, path dependencies without one get a pseudo version.
+pub fn resolve(dir: &Path, max_depth: usize) -> Result<Vec<StandalonePackage>> {
+    resolve_all(
+        vec![Package::from_wesl_toml_dir(dir, None)?],
+        HashSet::new(),
+        max_depth,
+    )
+}
+
+/// Resolves `roots` and their path dependencies up to `max_depth`, in depth-first order. Packages
+/// whose canonical directory is in `visited` are skipped, as are packages that are reachable
+/// through several paths after the first one.
+pub fn resolve_all(
+    roots: Vec<Package>,
+    mut visited: HashSet<PathBuf>,
+    max_depth: usize,
+) -> Result<Vec<StandalonePackage>> {
+    let mut packages = Vec::new();
+    let mut stack = roots
+        .into_iter()
+        .rev()
+        .map(|root| (root, 0))
+        .collect::<Vec<_>>();
+    while let Some((package, depth)) = stack.pop() {
+        if !visited.insert(canonical(&package.dir)?) {
+            continue;
+        }
+
+        let dependencies = path_dependencies(&package)?;
+        if depth < max_depth {
+            stack.extend(
+                dependencies
+                    .iter()
+                    .rev()
+                    .map(|dependency| (dependency.clone(), depth + 1)),
+            );
+        }
+
+        packages.push(StandalonePackage {
+            package,
+            dependencies,
+        });
+    }
+
+    Ok(packages)
+}
+
+/// The path dependencies declared in the `wesl.toml` of `package`, sorted by their local name.
+/// Fails for dependencies that would need a package manager to resolve.
+fn path_dependencies(package: &Package) -> Result<Vec<Package>> {
+    if package.wesl_toml.package.dependencies.is_some() {
+        bail!(
+            "package '{}' uses 'dependencies = \"auto\"', which requires a package manager",
+            package.package_name
+        );
+    }
+
+    let mut dependencies = package
+        .wesl_toml
+        .dependencies
+        .iter()
+        .map(|(local_name, dependency)| {
+            let path = dependency.path.as_ref().with_context(|| {
+                format!(
+                    "dependency '{local_name}' of package '{}' has no path, only path dependencies \
+                     are supported without a package manager",
+                    package.package_name
+                )
+            })?;
+            let mut resolved = Package::new_path_dependency(
+                &package.dir.join(path),
+                local_name.clone(),
+                dependency
+                    .package
+                    .clone()
+                    .unwrap_or_else(|| local_name.clone()),
+            )?;
+            // Without a package name in the dependency, the one in its own wesl.toml is used
+            if dependency.package.is_none()
+                && let Some(name) = &resolved.wesl_toml.package.name
+            {
+                resolved.package_name = name.clone();
+            }
+            Ok(resolved)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Sort dependencies for a stable order
+    dependencies.sort_by(|a, b| a.local_name.cmp(&b.local_name));
+
+    Ok(dependencies)
+}
+
+fn canonical(dir: &Path) -> Result<PathBuf> {
+    dir.canonicalize()
+        .with_context(|| format!("package directory {} not found", dir.display()))
+}
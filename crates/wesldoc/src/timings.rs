@@ -0,0 +1,78 @@
+use std::{fmt::Write, time::Duration};
+use wesldoc_ast::Version;
+
+/// Wall-clock times of a run, printed with `--timings`. Packages are compiled concurrently, so
+/// their compile times can add up to more than the total.
+#[derive(Debug, Default)]
+pub struct Timings {
+    /// Time spent resolving the packages, e.g. running `cargo metadata`.
+    pub resolve: Duration,
+    pub packages: Vec<PackageTimings>,
+}
+
+#[derive(Debug)]
+pub struct PackageTimings {
+    pub name: String,
+    pub version: Version,
+    pub compile: Duration,
+    pub generate: Duration,
+}
+
+impl Timings {
+    pub fn table(&self, total: Duration) -> String {
+        let rows = self
+            .packages
+            .iter()
+            .map(|package| {
+                (
+                    format!("{} v{}", package.name, package.version),
+                    package.compile,
+                    package.generate,
+                )
+            })
+            .collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|(name, _, _)| name.chars().count())
+            .chain(["Package".len(), "Resolve".len(), "Total".len()])
+            .max()
+            .unwrap_or_default();
+        let separator = format!("+-{}-+------------+------------+", "-".repeat(width));
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{separator}");
+        let _ = writeln!(
+            out,
+            "| {:<width$} | {:>10} | {:>10} |",
+            "Package", "Compile", "Generate"
+        );
+        let _ = writeln!(out, "{separator}");
+        row(&mut out, "Resolve", width, Some(self.resolve), None);
+        for (name, compile, generate) in &rows {
+            row(&mut out, name, width, Some(*compile), Some(*generate));
+        }
+        let _ = writeln!(out, "{separator}");
+        row(&mut out, "Total", width, Some(total), None);
+        let _ = writeln!(out, "{separator}");
+        out
+    }
+}
+
+fn row(
+    out: &mut String,
+    name: &str,
+    width: usize,
+    compile: Option<Duration>,
+    generate: Option<Duration>,
+) {
+    let duration = |duration: Option<Duration>| match duration {
+        Some(duration) => format!("{:>8.1}ms", duration.as_secs_f64() * 1000.0),
+        None => String::new(),
+    };
+    let _ = writeln!(
+        out,
+        "| {name:<width$} | {:>10} | {:>10} |",
+        duration(compile),
+        duration(generate)
+    );
+}
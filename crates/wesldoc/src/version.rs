@@ -0,0 +1,98 @@
+use crate::fingerprint;
+use anyhow::{Context, Result, bail};
+use std::{fs, path::Path};
+use wesldoc_ast::Version;
+
+/// Version of a package that is referenced by path. It is read from the package's own manifest
+/// (`Cargo.toml`, `wesl.toml` or `package.json`, in this order). Without a version, a pseudo
+/// version is derived from the content of the wesl files in `root`, so documenting the same
+/// sources always yields the same version.
+pub fn path_dependency_version(dir: &Path, root: &Path) -> Result<Version> {
+    if let Some(version) = manifest_version(dir)? {
+        return Ok(version);
+    }
+
+    Ok(Version::parse(&format!(
+        "0.0.0-local-{:016x}",
+        fingerprint::content_hash(root)?
+    ))?)
+}
+
+fn manifest_version(dir: &Path) -> Result<Option<Version>> {
+    let cargo_toml = dir.join("Cargo.toml");
+    if cargo_toml.is_file() {
+        let table = toml::from_slice::<toml::Table>(&fs::read(&cargo_toml)?)?;
+        match table
+            .get("package")
+            .and_then(|package| package.get("version"))
+        {
+            Some(toml::Value::String(version)) => return parse(version, &cargo_toml).map(Some),
+            // Inherited with `version.workspace = true`
+            Some(toml::Value::Table(version))
+                if version.get("workspace") == Some(&toml::Value::Boolean(true)) =>
+            {
+                return workspace_version(dir).map(Some);
+            }
+            _ => (),
+        }
+    }
+
+    let wesl_toml = dir.join("wesl.toml");
+    if wesl_toml.is_file() {
+        let table = toml::from_slice::<toml::Table>(&fs::read(&wesl_toml)?)?;
+        if let Some(version) = table
+            .get("package")
+            .and_then(|package| package.get("version"))
+            .and_then(|version| version.as_str())
+        {
+            return parse(version, &wesl_toml).map(Some);
+        }
+    }
+
+    let package_json = dir.join("package.json");
+    if package_json.is_file() {
+        let json = serde_json::from_slice::<serde_json::Value>(&fs::read(&package_json)?)?;
+        if let Some(version) = json.get("version").and_then(|version| version.as_str()) {
+            return parse(version, &package_json).map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Version in `workspace.package` of the closest workspace manifest above `dir`.
+fn workspace_version(dir: &Path) -> Result<Version> {
+    let dir = &dir.canonicalize()?;
+    for ancestor in dir.ancestors().skip(1) {
+        let cargo_toml = ancestor.join("Cargo.toml");
+        if !cargo_toml.is_file() {
+            continue;
+        }
+        let table = toml::from_slice::<toml::Table>(&fs::read(&cargo_toml)?)?;
+        let Some(workspace) = table.get("workspace") else {
+            continue;
+        };
+        let version = workspace
+            .get("package")
+            .and_then(|package| package.get("version"))
+            .and_then(|version| version.as_str())
+            .with_context(|| {
+                format!(
+                    "{} inherits the workspace version, but {} has no 'workspace.package.version'",
+                    dir.join("Cargo.toml").display(),
+                    cargo_toml.display()
+                )
+            })?;
+        return parse(version, &cargo_toml);
+    }
+
+    bail!(
+        "{} inherits the workspace version, but no workspace was found",
+        dir.join("Cargo.toml").display()
+    )
+}
+
+fn parse(version: &str, manifest: &Path) -> Result<Version> {
+    Version::parse(version)
+        .with_context(|| format!("invalid version '{version}' in {}", manifest.display()))
+}
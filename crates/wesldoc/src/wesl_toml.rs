@@ -1,12 +1,16 @@
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use serde::Deserialize;
 use std::{collections::HashMap, path::PathBuf};
+use wesldoc_ast::Version;
+use wesldoc_compiler::{Lint, LintLevel, LintLevels};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct WeslToml {
     pub package: WeslTomlPackage,
     #[serde(default)]
     pub dependencies: HashMap<String, WeslTomlDependency>,
+    #[serde(default)]
+    pub wesldoc: WeslTomlWesldoc,
 }
 
 impl WeslToml {
@@ -15,39 +19,58 @@ impl WeslToml {
             bail!("only edition 'unstable_2025' is supported");
         }
 
-        match self.package.package_manager {
-            Some(WeslTomlPackageManager::Cargo) | None => (),
-            Some(WeslTomlPackageManager::Npm) => {
-                bail!("npm package manager is not supported yet");
-            }
-        }
-
         if self.package.dependencies == Some(DependenciesAuto::Auto)
             && !self.dependencies.is_empty()
         {
             bail!("cannot have both 'dependencies = \"auto\"' and explicit dependencies");
         }
 
+        self.package.version()?;
+        self.lint_levels()?;
+
         Ok(())
     }
+
+    /// Lint levels from the `[wesldoc.lints]` table.
+    pub fn lint_levels(&self) -> Result<LintLevels> {
+        let mut levels = LintLevels::default();
+        for (name, level) in &self.wesldoc.lints {
+            let Some(lint) = Lint::from_name(name) else {
+                bail!("unknown lint '{name}' in [wesldoc.lints]");
+            };
+            let Some(level) = LintLevel::from_name(level) else {
+                bail!("unknown lint level '{level}' for lint '{name}'");
+            };
+            levels.set(lint, level);
+        }
+        Ok(levels)
+    }
 }
 
 impl Default for WeslToml {
     fn default() -> Self {
         Self {
             package: WeslTomlPackage {
+                name: None,
+                version: None,
                 edition: latest_known_edition(),
                 root: default_root(),
                 package_manager: None,
                 dependencies: Some(DependenciesAuto::Auto),
             },
             dependencies: HashMap::new(),
+            wesldoc: WeslTomlWesldoc::default(),
         }
     }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct WeslTomlPackage {
+    /// Name and version are only used for standalone packages that are not managed by Cargo.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
     #[serde(default = "latest_known_edition")]
     pub edition: String,
     #[serde(default = "default_root")]
@@ -58,6 +81,26 @@ pub struct WeslTomlPackage {
     pub dependencies: Option<DependenciesAuto>,
 }
 
+impl WeslTomlPackage {
+    pub fn version(&self) -> Result<Option<Version>> {
+        self.version
+            .as_deref()
+            .map(|version| {
+                Version::parse(version)
+                    .with_context(|| format!("invalid package version '{version}' in wesl.toml"))
+            })
+            .transpose()
+    }
+}
+
+/// Options only used by wesldoc.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WeslTomlWesldoc {
+    /// Lint names mapped to `allow`, `warn` or `deny`.
+    #[serde(default)]
+    pub lints: HashMap<String, String>,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
 pub enum WeslTomlPackageManager {
     #[serde(rename = "cargo")]
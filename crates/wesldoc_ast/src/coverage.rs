@@ -0,0 +1,228 @@
+use crate::{
+    DocComment, Function, Ident, IndexMap, Item, ItemInstance, ItemKind, Module, Struct, WeslDocs,
+    md,
+};
+use std::fmt;
+
+/// Documentation coverage of a package, per module and per kind of documentable element.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Modules in depth-first order, including modules without documentable elements.
+    pub modules: Vec<ModuleCoverage>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModuleCoverage {
+    /// Path of the module, starting with the package name.
+    pub path: Vec<String>,
+    /// Documented elements per kind. Kinds without elements are omitted.
+    pub kinds: IndexMap<CoverageKind, CoverageCount>,
+    /// Modules and items with a code example in their documentation.
+    pub examples: CoverageCount,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageCount {
+    pub documented: usize,
+    pub total: usize,
+}
+
+/// Kinds of documentable elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CoverageKind {
+    Module,
+    Constant,
+    Override,
+    GlobalVariable,
+    Struct,
+    StructMember,
+    Function,
+    /// A parameter counts as documented if the doc comment of its function mentions it as
+    /// inline code, e.g. `` `position` ``.
+    FunctionParameter,
+    TypeAlias,
+}
+
+impl Coverage {
+    pub fn of(docs: &WeslDocs) -> Self {
+        fn add_rec(modules: &mut Vec<ModuleCoverage>, module: &Module, path: &[String]) {
+            let mut path = path.to_vec();
+            path.push(module.name.clone());
+            modules.push(ModuleCoverage::of(module, path.clone()));
+            for submodule in &module.modules {
+                add_rec(modules, submodule, &path);
+            }
+        }
+
+        let mut modules = Vec::new();
+        add_rec(&mut modules, &docs.root, &[]);
+        Self { modules }
+    }
+
+    pub fn total(&self) -> CoverageCount {
+        self.modules.iter().map(ModuleCoverage::total).sum()
+    }
+
+    pub fn examples(&self) -> CoverageCount {
+        self.modules.iter().map(|module| module.examples).sum()
+    }
+
+    /// Documented elements per kind over all modules, sorted by kind.
+    pub fn kinds(&self) -> IndexMap<CoverageKind, CoverageCount> {
+        let mut kinds = IndexMap::<CoverageKind, CoverageCount>::new();
+        for module in &self.modules {
+            for (kind, count) in &module.kinds {
+                *kinds.entry(*kind).or_default() += *count;
+            }
+        }
+        kinds.sort_keys();
+        kinds
+    }
+}
+
+impl ModuleCoverage {
+    fn of(module: &Module, path: Vec<String>) -> Self {
+        let mut coverage = Self {
+            path,
+            ..Default::default()
+        };
+
+        // Modules without a source only group their submodules
+        if module.source.is_some() {
+            coverage.add(CoverageKind::Module, module.comment.as_ref());
+        }
+        coverage.add_items(&module.constants);
+        coverage.add_items(&module.overrides);
+        coverage.add_items(&module.global_variables);
+        coverage.add_items(&module.structs);
+        coverage.add_items(&module.functions);
+        coverage.add_items(&module.type_aliases);
+        for instance in module.structs.values().flat_map(|item| &item.instances) {
+            coverage.add_struct_members(instance);
+        }
+        for instance in module.functions.values().flat_map(|item| &item.instances) {
+            coverage.add_function_parameters(instance);
+        }
+
+        coverage
+    }
+
+    pub fn total(&self) -> CoverageCount {
+        self.kinds.values().copied().sum()
+    }
+
+    fn add(&mut self, kind: CoverageKind, comment: Option<&DocComment>) {
+        self.kinds.entry(kind).or_default().add(comment.is_some());
+        self.examples.add(comment.is_some_and(has_example));
+    }
+
+    fn add_items<T: ItemInstance>(&mut self, items: &IndexMap<Ident, Item<T>>) {
+        let kind = CoverageKind::from(T::ITEM_KIND);
+        for instance in items.values().flat_map(|item| &item.instances) {
+            self.add(kind, instance.comment());
+        }
+    }
+
+    fn add_struct_members(&mut self, instance: &Struct) {
+        for member in &instance.members {
+            self.kinds
+                .entry(CoverageKind::StructMember)
+                .or_default()
+                .add(member.comment.is_some());
+        }
+    }
+
+    fn add_function_parameters(&mut self, instance: &Function) {
+        for parameter in &instance.parameters {
+            let is_documented = instance
+                .comment
+                .as_ref()
+                .is_some_and(|comment| mentions_code(comment, &parameter.name.0));
+            self.kinds
+                .entry(CoverageKind::FunctionParameter)
+                .or_default()
+                .add(is_documented);
+        }
+    }
+}
+
+impl CoverageCount {
+    pub fn add(&mut self, is_documented: bool) {
+        self.documented += is_documented as usize;
+        self.total += 1;
+    }
+
+    /// Percentage of documented elements, 100% if there are none.
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.documented as f64 / self.total as f64 * 100.0
+    }
+}
+
+impl std::ops::AddAssign for CoverageCount {
+    fn add_assign(&mut self, other: Self) {
+        self.documented += other.documented;
+        self.total += other.total;
+    }
+}
+
+impl std::iter::Sum for CoverageCount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut acc, count| {
+            acc += count;
+            acc
+        })
+    }
+}
+
+impl CoverageKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            CoverageKind::Module => "module",
+            CoverageKind::Constant => "constant",
+            CoverageKind::Override => "override",
+            CoverageKind::GlobalVariable => "global_variable",
+            CoverageKind::Struct => "struct",
+            CoverageKind::StructMember => "struct_member",
+            CoverageKind::Function => "function",
+            CoverageKind::FunctionParameter => "function_parameter",
+            CoverageKind::TypeAlias => "type_alias",
+        }
+    }
+}
+
+impl From<ItemKind> for CoverageKind {
+    fn from(kind: ItemKind) -> Self {
+        match kind {
+            ItemKind::Module => CoverageKind::Module,
+            ItemKind::Constant => CoverageKind::Constant,
+            ItemKind::Override => CoverageKind::Override,
+            ItemKind::GlobalVariable => CoverageKind::GlobalVariable,
+            ItemKind::Struct => CoverageKind::Struct,
+            ItemKind::Function => CoverageKind::Function,
+            ItemKind::TypeAlias => CoverageKind::TypeAlias,
+        }
+    }
+}
+
+impl fmt::Display for CoverageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn has_example(comment: &DocComment) -> bool {
+    comment
+        .unsafe_full
+        .iter()
+        .any(|event| matches!(event, md::Event::Start(md::Tag::CodeBlock(_))))
+}
+
+fn mentions_code(comment: &DocComment, name: &str) -> bool {
+    comment
+        .unsafe_full
+        .iter()
+        .any(|event| matches!(event, md::Event::Code(code) if code.as_ref() == name))
+}
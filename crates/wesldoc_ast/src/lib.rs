@@ -1,5 +1,8 @@
+mod coverage;
+
 use std::{fmt, str::FromStr};
 
+pub use self::coverage::{Coverage, CoverageCount, CoverageKind, ModuleCoverage};
 pub use indexmap::{IndexMap, IndexSet};
 pub use pulldown_cmark as md;
 pub use semver::Version;
@@ -14,6 +17,8 @@ pub struct WeslDocs {
 pub struct Module {
     pub name: String,
     pub source: Option<String>,
+    /// Set if the module failed to compile. Only the raw source is available in that case.
+    pub error: Option<String>,
     pub comment: Option<DocComment>,
     pub modules: Vec<Module>,
     pub constants: IndexMap<Ident, Item<Constant>>,
@@ -22,7 +27,11 @@ pub struct Module {
     pub structs: IndexMap<Ident, Item<Struct>>,
     pub functions: IndexMap<Ident, Item<Function>>,
     pub type_aliases: IndexMap<Ident, Item<TypeAlias>>,
+    pub re_exports: Vec<ReExport>,
+    pub directives: Vec<Directive>,
+    pub const_asserts: Vec<ConstAssert>,
     pub translate_time_features: IndexSet<String>,
+    pub required_extensions: IndexSet<String>,
 }
 
 impl Module {
@@ -30,6 +39,7 @@ impl Module {
         Self {
             name,
             source: None,
+            error: None,
             comment: None,
             modules: Vec::new(),
             constants: IndexMap::new(),
@@ -38,11 +48,62 @@ impl Module {
             structs: IndexMap::new(),
             functions: IndexMap::new(),
             type_aliases: IndexMap::new(),
+            re_exports: Vec::new(),
+            directives: Vec::new(),
+            const_asserts: Vec::new(),
             translate_time_features: IndexSet::new(),
+            required_extensions: IndexSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReExport {
+    /// The name under which the item is re-exported.
+    pub name: Ident,
+    pub kind: ItemKind,
+    /// The name of the item in the module where it is defined.
+    pub original_name: Ident,
+    /// The module where the item is defined. For re-exported modules, this is the path of the
+    /// module itself.
+    pub def_path: DefinitionPath,
+    pub conditional: Option<Conditional>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Directive {
+    pub kind: DirectiveKind,
+    pub conditional: Option<Conditional>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DirectiveKind {
+    Enable(Vec<String>),
+    Requires(Vec<String>),
+    Diagnostic {
+        severity: DiagnosticSeverity,
+        rule: String,
+    },
+}
+
+impl DirectiveKind {
+    pub fn extensions(&self) -> &[String] {
+        match self {
+            DirectiveKind::Enable(extensions) | DirectiveKind::Requires(extensions) => extensions,
+            DirectiveKind::Diagnostic { .. } => &[],
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct ConstAssert {
+    /// The asserted expression as written in the source.
+    pub expression: String,
+    pub conditional: Option<Conditional>,
+    pub comment: Option<DocComment>,
+    pub span: Option<Span>,
+}
+
 #[derive(Debug, Clone)]
 pub enum Conditional {
     False,
@@ -218,7 +279,7 @@ pub enum AddressSpace {
     Private,
     WorkGroup,
     Uniform,
-    Storage { load: bool, store: bool },
+    Storage(AccessMode),
     Handle,
     Immediate,
     PushConstant,
@@ -232,13 +293,8 @@ impl fmt::Display for AddressSpace {
             AddressSpace::Private => write!(f, "<private>"),
             AddressSpace::WorkGroup => write!(f, "<workgroup>"),
             AddressSpace::Uniform => write!(f, "<uniform>"),
-            AddressSpace::Storage { store, .. } => {
-                if *store {
-                    write!(f, "<storage, read_write>")
-                } else {
-                    write!(f, "<storage>")
-                }
-            }
+            AddressSpace::Storage(AccessMode::Read) => write!(f, "<storage>"),
+            AddressSpace::Storage(access_mode) => write!(f, "<storage, {access_mode}>"),
             AddressSpace::Handle => write!(f, ""),
             AddressSpace::Immediate => write!(f, "<immediate>"),
             AddressSpace::PushConstant => write!(f, "<push_constant>"),
@@ -247,6 +303,25 @@ impl fmt::Display for AddressSpace {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    Read,
+    Write,
+    ReadWrite,
+    Atomic,
+}
+
+impl fmt::Display for AccessMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessMode::Read => write!(f, "read"),
+            AccessMode::Write => write!(f, "write"),
+            AccessMode::ReadWrite => write!(f, "read_write"),
+            AccessMode::Atomic => write!(f, "atomic"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Expression {
     Literal(Literal),
@@ -331,6 +406,8 @@ pub enum TypeExpression {
         kind: ItemKind,
         def_path: DefinitionPath,
     },
+    /// `atomic<T>`
+    Atomic(Box<TypeExpression>),
 }
 
 #[derive(Debug, Clone)]
@@ -415,6 +492,9 @@ pub struct DocComment {
     pub unsafe_short: Vec<md::Event<'static>>,
     /// This is not escaped, e.g. it can contain `<script>` tags.
     pub unsafe_short_no_links: Vec<md::Event<'static>>,
+    /// The version set with an `@since 0.2.0` line, which overrides the computed version the
+    /// item was introduced in.
+    pub since: Option<Version>,
 }
 
 #[derive(Debug, Clone)]
@@ -1,10 +1,11 @@
-use crate::{Context, build_expression, map};
+use crate::{Context, build_expression, lints::is_wesldoc_attribute, map};
 use wesl::syntax;
 use wesldoc_ast::*;
 
 pub fn build_attributes(attributes: &[syntax::AttributeNode], ctx: &Context) -> Vec<Attribute> {
     attributes
         .iter()
+        .filter(|attr| !is_wesldoc_attribute(attr))
         .filter_map(|attr| build_attribute(attr, ctx))
         .collect()
 }
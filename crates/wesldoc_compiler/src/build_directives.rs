@@ -0,0 +1,40 @@
+use crate::{
+    Context,
+    build_conditional::{ConditionalScope, build_conditional},
+    map,
+};
+use wesl::syntax;
+use wesldoc_ast::*;
+
+pub fn build_directives(ctx: &Context) -> Vec<Directive> {
+    // `wesl` adds the directives of imported modules, only the module's own ones are listed
+    let mut conditional_scope = ConditionalScope::new();
+    ctx.own_syntax()
+        .global_directives
+        .iter()
+        .map(|directive| build_directive(directive, &mut conditional_scope))
+        .collect()
+}
+
+fn build_directive(
+    directive: &syntax::GlobalDirective,
+    conditional_scope: &mut ConditionalScope,
+) -> Directive {
+    match directive {
+        syntax::GlobalDirective::Diagnostic(diagnostic) => Directive {
+            kind: DirectiveKind::Diagnostic {
+                severity: map(&diagnostic.severity),
+                rule: diagnostic.rule_name.clone(),
+            },
+            conditional: build_conditional(conditional_scope, &diagnostic.attributes),
+        },
+        syntax::GlobalDirective::Enable(enable) => Directive {
+            kind: DirectiveKind::Enable(enable.extensions.clone()),
+            conditional: build_conditional(conditional_scope, &enable.attributes),
+        },
+        syntax::GlobalDirective::Requires(requires) => Directive {
+            kind: DirectiveKind::Requires(requires.extensions.clone()),
+            conditional: build_conditional(conditional_scope, &requires.attributes),
+        },
+    }
+}
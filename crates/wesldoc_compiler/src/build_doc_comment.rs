@@ -1,4 +1,4 @@
-use crate::{Context, ResolveTarget};
+use crate::{Context, Lint, ResolveTarget, lints::is_lint_line};
 use wesldoc_ast::*;
 
 pub fn build_inner_doc_comment(raw_comment: &str, ctx: &Context) -> Option<DocComment> {
@@ -11,12 +11,33 @@ pub fn build_outer_doc_comment(raw_comment: &str, ctx: &Context) -> Option<DocCo
 
 fn build_doc_comment(raw_comment: &str, comment_prefix: &str, ctx: &Context) -> Option<DocComment> {
     // Strip the comment prefix
-    let comment = raw_comment
+    let lines = raw_comment
         .lines()
         .filter_map(|line| {
             let line = line.trim_start();
             line.starts_with(comment_prefix).then(|| &line[3..])
         })
+        .filter(|line| comment_prefix != "//!" || !is_lint_line(line))
+        .collect::<Vec<_>>();
+
+    // Take the `@since` tag out of the rendered comment
+    let since = lines
+        .iter()
+        .find_map(|line| since_tag(line))
+        .and_then(|since| match since.parse::<Version>() {
+            Ok(version) => Some(version),
+            Err(err) => {
+                ctx.report(
+                    Lint::InvalidDocTags,
+                    None,
+                    format!("invalid version `{since}` in `@since` tag: {err}"),
+                );
+                None
+            }
+        });
+    let comment = lines
+        .into_iter()
+        .filter(|line| since_tag(line).is_none())
         .enumerate()
         .fold(String::new(), |mut acc, (idx, line)| {
             if idx != 0 {
@@ -37,7 +58,7 @@ fn build_doc_comment(raw_comment: &str, comment_prefix: &str, ctx: &Context) ->
     )
     .map(|event| event.into_static())
     .collect::<Vec<_>>();
-    if full.is_empty() {
+    if full.is_empty() && since.is_none() {
         return None;
     }
 
@@ -105,6 +126,7 @@ fn build_doc_comment(raw_comment: &str, comment_prefix: &str, ctx: &Context) ->
         unsafe_full: full,
         unsafe_short: short,
         unsafe_short_no_links: short_no_links,
+        since,
     })
 }
 
@@ -133,7 +155,7 @@ fn raise_heading_level(level: md::HeadingLevel) -> md::HeadingLevel {
     }
 }
 
-// TODO: Only works for items in scope as this just looks up the name in the source map
+// TODO: Only works for items that are part of the compiled module, i.e. local or used items
 fn resolve_intra_doc_links(events: &mut [md::Event], ctx: &Context) {
     for event in events {
         if let md::Event::Start(md::Tag::Link { dest_url, .. }) = event {
@@ -147,9 +169,24 @@ fn resolve_intra_doc_links(events: &mut [md::Event], ctx: &Context) {
                 }
                 .to_string()
                 .into();
-            } else {
-                log::warn!("Failed to resolve intra-doc link: {dest_url}");
+            } else if !is_external_link(dest_url) {
+                ctx.report(
+                    Lint::BrokenIntraDocLinks,
+                    None,
+                    format!("failed to resolve intra-doc link `{dest_url}`"),
+                );
             }
         }
     }
 }
+
+fn is_external_link(dest_url: &str) -> bool {
+    dest_url.contains("://") || dest_url.starts_with(['#', '/']) || dest_url.starts_with("mailto:")
+}
+
+/// The version of a `@since 0.2.0` line.
+fn since_tag(line: &str) -> Option<&str> {
+    let version = line.trim().strip_prefix("@since")?;
+    let version = version.strip_prefix(char::is_whitespace)?.trim();
+    (!version.is_empty() && !version.contains(char::is_whitespace)).then_some(version)
+}
@@ -0,0 +1,151 @@
+use crate::{
+    Context, Lint,
+    build_conditional::{ConditionalScope, build_conditional},
+    context::{decl_attributes, decl_ident_and_kind, flatten_imports},
+    map,
+};
+use wesl::{ModulePath, syntax};
+use wesldoc_ast::*;
+
+// Guards against cyclic re-exports.
+pub(crate) const MAX_RE_EXPORT_DEPTH: usize = 32;
+
+pub fn build_re_exports(ctx: &Context) -> Vec<ReExport> {
+    let mut re_exports = Vec::new();
+
+    for (path, item, rename, conditional) in collect_exports(ctx.module_path(), ctx.imports()) {
+        let Some(resolved) = resolve_re_export(&path, &item, ctx, 0) else {
+            ctx.report(
+                Lint::UnresolvedReExports,
+                None,
+                format!(
+                    "failed to resolve re-export `{}::{}`",
+                    path.components.join("::"),
+                    item
+                ),
+            );
+            continue;
+        };
+        let Some(def_path) = ctx.def_path(&resolved.path) else {
+            continue;
+        };
+
+        re_exports.push(ReExport {
+            name: rename,
+            kind: resolved.kind,
+            original_name: resolved.name,
+            def_path,
+            conditional: and(conditional, resolved.conditional),
+        });
+    }
+
+    re_exports
+}
+
+struct Resolved {
+    kind: ItemKind,
+    path: ModulePath,
+    name: Ident,
+    conditional: Option<Conditional>,
+}
+
+/// Follows re-exports until the module that defines the item is found. The conditional of the
+/// item is taken from the defining module.
+fn resolve_re_export(
+    path: &ModulePath,
+    name: &Ident,
+    ctx: &Context,
+    depth: usize,
+) -> Option<Resolved> {
+    if depth > MAX_RE_EXPORT_DEPTH {
+        return None;
+    }
+
+    if let Some(translation_unit) = ctx.get_module_syntax(path) {
+        // Defined in this module
+        let mut conditional_scope = ConditionalScope::new();
+        let mut kind = None;
+        let mut conditionals = Vec::new();
+        for decl in &translation_unit.global_declarations {
+            let conditional = build_conditional(&mut conditional_scope, decl_attributes(decl));
+            if let Some((ident, decl_kind)) = decl_ident_and_kind(decl)
+                && map(ident) == *name
+            {
+                kind = Some(decl_kind);
+                conditionals.push(conditional);
+            }
+        }
+        if let Some(kind) = kind {
+            return Some(Resolved {
+                kind,
+                path: path.clone(),
+                name: name.clone(),
+                conditional: or_all(conditionals),
+            });
+        }
+
+        // Re-exported by this module
+        for (inner_path, item, rename, conditional) in
+            collect_exports(path, &translation_unit.imports)
+        {
+            if rename == *name {
+                let mut resolved = resolve_re_export(&inner_path, &item, ctx, depth + 1)?;
+                resolved.conditional = and(conditional, resolved.conditional);
+                return Some(resolved);
+            }
+        }
+    }
+
+    // Re-exported module
+    let mut module_path = path.clone();
+    module_path.components.push(name.0.clone());
+    if ctx.get_module_syntax(&module_path).is_some() {
+        return Some(Resolved {
+            kind: ItemKind::Module,
+            path: module_path,
+            name: name.clone(),
+            conditional: None,
+        });
+    }
+
+    None
+}
+
+/// Collects all `@publish` imports as `(path, item, rename, conditional)`. The paths are resolved
+/// relative to `module_path`.
+fn collect_exports(
+    module_path: &ModulePath,
+    imports: &[syntax::ImportStatement],
+) -> Vec<(ModulePath, Ident, Ident, Option<Conditional>)> {
+    // The conditional scope spans all import statements, not only the exported ones.
+    let mut conditional_scope = ConditionalScope::new();
+    let conditionals = imports
+        .iter()
+        .map(|import| build_conditional(&mut conditional_scope, &import.attributes))
+        .collect::<Vec<_>>();
+
+    flatten_imports(module_path, imports)
+        .into_iter()
+        .filter(|(idx, ..)| {
+            imports[*idx]
+                .attributes
+                .iter()
+                .any(|attr| **attr == syntax::Attribute::Publish)
+        })
+        .map(|(idx, path, item, rename)| (path, item, rename, conditionals[idx].clone()))
+        .collect()
+}
+
+fn and(left: Option<Conditional>, right: Option<Conditional>) -> Option<Conditional> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(Conditional::And(Box::new(left), Box::new(right))),
+        (left, right) => left.or(right),
+    }
+}
+
+// This is `None` if any of the conditionals is `None`.
+fn or_all(conditionals: Vec<Option<Conditional>>) -> Option<Conditional> {
+    conditionals
+        .into_iter()
+        .reduce(|acc, c| Some(Conditional::Or(Box::new(acc?), Box::new(c?))))?
+}
@@ -4,14 +4,22 @@ use wesldoc_ast::*;
 
 pub fn build_type(ty: &syntax::TypeExpression, ctx: &Context) -> TypeExpression {
     let name = ty.ident.name().clone();
+    let target = match &ty.path {
+        Some(path) => ResolveTarget::Qualified(path, &name),
+        None => ResolveTarget::MaybeMangled(&name),
+    };
 
-    match ctx.resolve_reference(ResolveTarget::MaybeMangled(&name)) {
-        Some((name, kind, def_path)) => TypeExpression::Referenced {
+    if let Some((name, kind, def_path)) = ctx.resolve_reference(target) {
+        return TypeExpression::Referenced {
             name,
             kind,
             def_path,
-        },
-        None => TypeExpression::TypeIdentifier {
+        };
+    }
+
+    match atomic_inner_type(ty) {
+        Some(inner) if name == "atomic" => TypeExpression::Atomic(Box::new(build_type(inner, ctx))),
+        _ => TypeExpression::TypeIdentifier {
             name: Ident(name),
             template_args: ty.template_args.as_ref().map(|args| {
                 args.iter()
@@ -21,3 +29,13 @@ pub fn build_type(ty: &syntax::TypeExpression, ctx: &Context) -> TypeExpression
         },
     }
 }
+
+fn atomic_inner_type(ty: &syntax::TypeExpression) -> Option<&syntax::TypeExpression> {
+    match ty.template_args.as_deref()? {
+        [arg] => match arg.expression.node() {
+            syntax::Expression::TypeOrIdentifier(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -5,10 +5,15 @@ use wesldoc_ast::IndexSet;
 pub fn collect_features(ctx: &Context) -> IndexSet<String> {
     let mut features = IndexSet::new();
 
-    for directive in &ctx.compiled().syntax.global_directives {
+    for directive in &ctx.own_syntax().global_directives {
         collect_from_global_directive(directive, &mut features);
     }
-    for decl in &ctx.compiled().syntax.global_declarations {
+    for decl in &ctx.own_syntax().global_declarations {
+        if let GlobalDeclaration::ConstAssert(const_assert) = decl.node() {
+            collect_from_attributes(&const_assert.attributes, &mut features);
+        }
+    }
+    for decl in &ctx.syntax().global_declarations {
         if ctx.as_local(decl).is_none() {
             continue;
         }
@@ -35,7 +40,7 @@ fn collect_from_global_directive(directive: &GlobalDirective, features: &mut Ind
 fn collect_from_global_declaration(decl: &GlobalDeclaration, features: &mut IndexSet<String>) {
     match decl {
         GlobalDeclaration::Void => (),
-        GlobalDeclaration::Compound(_) => panic!("compound should have been flattened"),
+        GlobalDeclaration::Compound(_) => (), // Not supported, reported when building the module
         GlobalDeclaration::Declaration(declaration) => {
             collect_from_attributes(&declaration.attributes, features);
         }
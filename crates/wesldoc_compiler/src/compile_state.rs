@@ -1,46 +1,30 @@
-use crate::Error;
-use std::cell::RefCell;
+use crate::Diagnostic;
+use std::sync::Mutex;
+use wesldoc_ast::IndexSet;
 
+/// State shared by all modules of a package while it is compiled. Modules that are compiled on
+/// other threads use their own state, which is merged back with [`CompileState::merge`] to keep
+/// the order of diagnostics independent of scheduling.
 #[derive(Debug, Default)]
 pub struct CompileState {
-    stats: RefCell<CompileStats>,
-    error: RefCell<Option<Error>>,
+    diagnostics: Mutex<IndexSet<Diagnostic>>,
 }
 
 impl CompileState {
-    pub fn track_documented(&self, is_documented: bool) {
-        let mut stats = self.stats.borrow_mut();
-        stats.documented.0 += is_documented as usize;
-        stats.documented.1 += 1;
+    // Identical diagnostics are only reported once, e.g. for repeated references to a missing
+    // dependency.
+    pub fn report(&self, diagnostic: Diagnostic) {
+        self.diagnostics.lock().unwrap().insert(diagnostic);
     }
 
-    // TODO: currently uses first error. maybe add priority to errors? or collect all?
-    pub fn report_error(&self, error: Error) {
-        let mut current = self.error.borrow_mut();
-        if current.is_none() {
-            *current = Some(error);
+    /// Reports all diagnostics of `other` as if they were found after the current ones.
+    pub fn merge(&self, other: CompileState) {
+        for diagnostic in other.finish() {
+            self.report(diagnostic);
         }
     }
 
-    pub fn into_result(self) -> Result<CompileStats, Error> {
-        match self.error.into_inner() {
-            Some(error) => Err(error),
-            None => Ok(self.stats.into_inner()),
-        }
-    }
-}
-
-#[derive(Debug, Default)]
-pub struct CompileStats {
-    documented: (usize, usize), // (documented, total)
-}
-
-impl CompileStats {
-    pub fn documented_percentage(&self) -> f64 {
-        let (documented, total) = self.documented;
-        if total == 0 {
-            return 100.0;
-        }
-        documented as f64 / total as f64 * 100.0
+    pub fn finish(self) -> Vec<Diagnostic> {
+        self.diagnostics.into_inner().unwrap().into_iter().collect()
     }
 }
@@ -1,73 +1,60 @@
-use crate::{CompileOptions, compile_state::CompileState, map::map};
-use std::collections::HashMap;
-use wesl::{CompileResult, Mangler, ModulePath, SourceMap as _, syntax};
+use crate::{
+    Diagnostic, Lint,
+    compile_state::CompileState,
+    lints::{LintLevels, item_lint_overrides, module_lint_overrides},
+    map::map,
+    parsed::ParsedSources,
+};
+use std::{cell::RefCell, collections::HashMap, ops::Range};
+use wesl::{CompileResult, ModulePath, SourceMap as _, syntax};
 use wesldoc_ast::{DefinitionPath, Ident, ItemKind, Version};
 use wgsl_parse::SyntaxNode;
 
 pub struct Context<'a> {
-    compiled: &'a CompileResult,
-    exports: HashMap<(ModulePath, Ident), Ident>, // (path, item_name) -> rename.unwrap_or(item_name)
+    syntax: &'a syntax::TranslationUnit,
+    imports: &'a [syntax::ImportStatement],
+    sources: Sources<'a>,
 
     module_path: ModulePath,
     dependencies: &'a HashMap<String, (String, Version)>,
 
     local: HashMap<String, ItemKind>,
     local_path: ModulePath,
+    imported: HashMap<String, (ModulePath, String)>, // local name -> (module path, item name)
+
+    lints: LintLevels,
+    item_lints: RefCell<Option<LintLevels>>,
 
-    compile_options: &'a CompileOptions,
     compile_state: &'a CompileState,
 }
 
+/// Where sources and the origins of declarations are looked up.
+enum Sources<'a> {
+    /// The module was compiled by `wesl`, imported declarations are mangled into the module and
+    /// the source map is used to find their origin. The syntax trees of the modules are the ones
+    /// `wesl` resolved.
+    Compiled(&'a CompileResult, &'a ParsedSources<'a>),
+    /// The module was parsed directly, imports are resolved by wesldoc.
+    Parsed(&'a ParsedSources<'a>),
+}
+
 impl Context<'_> {
     pub fn init<'a>(
-        imports: &[syntax::ImportStatement],
+        imports: &'a [syntax::ImportStatement],
         compiled: &'a CompileResult,
+        sources: &'a ParsedSources<'a>,
         module_path: ModulePath,
         dependencies: &'a HashMap<String, (String, Version)>,
-
-        compile_options: &'a CompileOptions,
+        lints: &LintLevels,
         compile_state: &'a CompileState,
     ) -> Context<'a> {
-        // Warn if the source map is not found
-        if compiled.sourcemap.is_none() {
-            log::warn!("no source map found for module {module_path:?}");
-        }
-
-        // Collect exports
-        let exports = collect_exports(imports);
-
         // Build local items
         let local = compiled
             .syntax
             .global_declarations
             .iter()
             .filter_map(|decl| {
-                let (ident, kind) = match decl.node() {
-                    syntax::GlobalDeclaration::Void => return None,
-                    syntax::GlobalDeclaration::Compound(_) => {
-                        panic!("compound should have been flattened")
-                    }
-                    syntax::GlobalDeclaration::Declaration(declaration) => match declaration.kind {
-                        syntax::DeclarationKind::Const => (&declaration.ident, ItemKind::Constant),
-                        syntax::DeclarationKind::Override => {
-                            (&declaration.ident, ItemKind::Override)
-                        }
-                        syntax::DeclarationKind::Let => return None, // should be unreachable?
-                        syntax::DeclarationKind::Var(_) => {
-                            (&declaration.ident, ItemKind::GlobalVariable)
-                        }
-                    },
-                    syntax::GlobalDeclaration::TypeAlias(type_alias) => {
-                        (&type_alias.ident, ItemKind::TypeAlias)
-                    }
-                    syntax::GlobalDeclaration::Struct(struct_) => {
-                        (&struct_.ident, ItemKind::Struct)
-                    }
-                    syntax::GlobalDeclaration::Function(function) => {
-                        (&function.ident, ItemKind::Function)
-                    }
-                    syntax::GlobalDeclaration::ConstAssert(_const_assert) => return None,
-                };
+                let (ident, kind) = decl_ident_and_kind(decl)?;
 
                 if compiled
                     .sourcemap
@@ -82,9 +69,75 @@ impl Context<'_> {
             })
             .collect();
 
-        Context {
-            compiled,
-            exports,
+        let ctx = Self::new(
+            &compiled.syntax,
+            imports,
+            Sources::Compiled(compiled, sources),
+            local,
+            module_path,
+            dependencies,
+            lints,
+            compile_state,
+        );
+
+        // Warn if the source map is not found
+        if compiled.sourcemap.is_none() {
+            ctx.report(Lint::MissingSourceMap, None, "no source map found");
+        }
+
+        ctx
+    }
+
+    /// Initializes the context for a module that was parsed without a source map. All
+    /// declarations of the module are local.
+    pub fn init_parsed<'a>(
+        syntax: &'a syntax::TranslationUnit,
+        sources: &'a ParsedSources<'a>,
+        module_path: ModulePath,
+        dependencies: &'a HashMap<String, (String, Version)>,
+        lints: &LintLevels,
+        compile_state: &'a CompileState,
+    ) -> Context<'a> {
+        let local = syntax
+            .global_declarations
+            .iter()
+            .filter_map(|decl| decl_ident_and_kind(decl))
+            .map(|(ident, kind)| (ident.name().to_string(), kind))
+            .collect();
+
+        Self::new(
+            syntax,
+            &syntax.imports,
+            Sources::Parsed(sources),
+            local,
+            module_path,
+            dependencies,
+            lints,
+            compile_state,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new<'a>(
+        syntax: &'a syntax::TranslationUnit,
+        imports: &'a [syntax::ImportStatement],
+        sources: Sources<'a>,
+        local: HashMap<String, ItemKind>,
+        module_path: ModulePath,
+        dependencies: &'a HashMap<String, (String, Version)>,
+        lints: &LintLevels,
+        compile_state: &'a CompileState,
+    ) -> Context<'a> {
+        // Build imported items and modules
+        let imported = flatten_imports(&module_path, imports)
+            .into_iter()
+            .map(|(_, path, item, rename)| (rename.0, (path, item.0)))
+            .collect();
+
+        let mut ctx = Context {
+            syntax,
+            imports,
+            sources,
 
             module_path,
             dependencies,
@@ -94,43 +147,93 @@ impl Context<'_> {
                 origin: syntax::PathOrigin::Relative(0),
                 components: Vec::new(),
             },
+            imported,
+
+            lints: lints.clone(),
+            item_lints: RefCell::new(None),
 
-            compile_options,
             compile_state,
+        };
+
+        // Apply module level lint overrides, they are inherited by submodules
+        let overrides = ctx
+            .get_source()
+            .map(module_lint_overrides)
+            .unwrap_or_default();
+        let mut unknown = Vec::new();
+        for lint_override in overrides {
+            match lint_override {
+                Ok((level, lint)) => ctx.lints.set(lint, level),
+                Err(message) => unknown.push(message),
+            }
+        }
+        for message in unknown {
+            ctx.report(Lint::UnknownLints, None, message);
         }
+
+        ctx
     }
 
-    pub fn at_path(&self, path: ModulePath) -> Context<'_> {
-        Context {
-            compiled: self.compiled,
-            exports: self.exports.clone(),
-            module_path: path,
-            dependencies: self.dependencies,
-            local: self.local.clone(),
-            local_path: self.local_path.clone(),
-            compile_options: self.compile_options,
-            compile_state: self.compile_state,
-        }
+    pub fn syntax(&self) -> &syntax::TranslationUnit {
+        self.syntax
+    }
+
+    pub fn imports(&self) -> &[syntax::ImportStatement] {
+        self.imports
     }
 
-    pub fn compiled(&self) -> &CompileResult {
-        self.compiled
+    pub fn module_path(&self) -> &ModulePath {
+        &self.module_path
     }
 
-    pub fn compile_options(&self) -> &CompileOptions {
-        self.compile_options
+    /// Lint levels of the module, including its module level overrides.
+    pub fn lints(&self) -> &LintLevels {
+        &self.lints
     }
 
-    pub fn compile_state(&self) -> &CompileState {
-        self.compile_state
+    /// Applies the `@wesldoc(...)` lint overrides of an item until the returned scope is dropped.
+    #[must_use]
+    pub fn enter_item(&self, attributes: &[syntax::AttributeNode]) -> ItemScope<'_> {
+        let mut lints = self.lints.clone();
+        let mut unknown = Vec::new();
+        for lint_override in item_lint_overrides(attributes) {
+            match lint_override {
+                Ok((level, lint)) => lints.set(lint, level),
+                Err(message) => unknown.push(message),
+            }
+        }
+        *self.item_lints.borrow_mut() = Some(lints);
+        for message in unknown {
+            self.report(Lint::UnknownLints, None, message);
+        }
+        ItemScope {
+            item_lints: &self.item_lints,
+        }
+    }
+
+    /// Reports a diagnostic for the current module, with the severity given by the lint level of
+    /// the current item or module.
+    pub fn report(&self, lint: Lint, span: Option<Range<usize>>, message: impl Into<String>) {
+        let level = match &*self.item_lints.borrow() {
+            Some(item_lints) => item_lints.get(lint),
+            None => self.lints.get(lint),
+        };
+        let Some(severity) = level.severity() else {
+            return;
+        };
+        self.compile_state.report(Diagnostic {
+            severity,
+            lint,
+            module_path: self.module_path.components.clone(),
+            span,
+            message: message.into(),
+        });
     }
 
     pub fn as_local(&self, decl: &syntax::GlobalDeclaration) -> Option<Ident> {
         let decl = match decl {
             syntax::GlobalDeclaration::Void => return None,
-            syntax::GlobalDeclaration::Compound(_) => {
-                panic!("compound should have been flattened")
-            }
+            syntax::GlobalDeclaration::Compound(_) => return None,
             syntax::GlobalDeclaration::Declaration(declaration) => &declaration.ident,
             syntax::GlobalDeclaration::TypeAlias(type_alias) => &type_alias.ident,
             syntax::GlobalDeclaration::Struct(struct_) => &struct_.ident,
@@ -141,33 +244,34 @@ impl Context<'_> {
         self.local.contains_key(&name.0).then_some(name)
     }
 
+    /// The syntax tree of the module as it was written. Unlike [`Context::syntax`], it doesn't
+    /// contain the declarations and directives `wesl` adds from imported modules, so it is used
+    /// for everything that can't be told apart by its name, e.g. `const_assert`s.
+    pub fn own_syntax(&self) -> &syntax::TranslationUnit {
+        self.get_module_syntax(&self.module_path)
+            .unwrap_or(self.syntax)
+    }
+
     pub fn get_source(&self) -> Option<&str> {
-        self.compiled
-            .sourcemap
-            .as_ref()
-            .and_then(|s| s.get_source(&self.module_path))
+        self.get_module_source(&self.module_path)
     }
 
-    pub fn as_export(&self, decl: &syntax::GlobalDeclaration) -> Option<(ModulePath, &Ident)> {
-        let decl = match decl {
-            syntax::GlobalDeclaration::Void => return None,
-            syntax::GlobalDeclaration::Compound(_) => {
-                panic!("compound should have been flattened")
+    pub fn get_module_source(&self, path: &ModulePath) -> Option<&str> {
+        match &self.sources {
+            Sources::Compiled(compiled, _) => {
+                compiled.sourcemap.as_ref().and_then(|s| s.get_source(path))
             }
-            syntax::GlobalDeclaration::Declaration(declaration) => &declaration.ident,
-            syntax::GlobalDeclaration::TypeAlias(type_alias) => &type_alias.ident,
-            syntax::GlobalDeclaration::Struct(struct_) => &struct_.ident,
-            syntax::GlobalDeclaration::Function(function) => &function.ident,
-            syntax::GlobalDeclaration::ConstAssert(_const_assert) => return None,
-        };
-
-        // TODO: This assumes the escape mangler was used.
-        let mangler = wesl::EscapeMangler;
-        let (path, name) = mangler.unmangle(&decl.name())?;
-
-        let local_name = self.exports.get(&(path.clone(), Ident(name)))?;
+            Sources::Parsed(sources) => sources.get(path).map(|(source, _)| source),
+        }
+    }
 
-        Some((path, local_name))
+    /// The syntax tree of a module, which was parsed once when the module was resolved.
+    pub fn get_module_syntax(&self, path: &ModulePath) -> Option<&syntax::TranslationUnit> {
+        match &self.sources {
+            Sources::Compiled(_, sources) | Sources::Parsed(sources) => {
+                sources.get(path).map(|(_, syntax)| syntax)
+            }
+        }
     }
 
     pub fn resolve_reference(
@@ -175,68 +279,168 @@ impl Context<'_> {
         target: ResolveTarget,
     ) -> Option<(Ident, ItemKind, DefinitionPath)> {
         let (name, kind, path) = self.get_decl(target)?;
-        let def_path = match &path.origin {
-            syntax::PathOrigin::Absolute => DefinitionPath::Absolute(path.components.clone()),
+        let def_path = self.def_path(path)?;
+        Some((Ident(name), kind, def_path))
+    }
+
+    pub fn def_path(&self, path: &ModulePath) -> Option<DefinitionPath> {
+        match &path.origin {
+            syntax::PathOrigin::Absolute => Some(DefinitionPath::Absolute(path.components.clone())),
             syntax::PathOrigin::Relative(n) => {
                 if self.module_path.components.len() < *n {
-                    log::warn!(
-                        "invalid relative path for type {} in module {}",
-                        name,
-                        self.module_path.components.join("/")
+                    self.report(
+                        Lint::UnresolvedPaths,
+                        None,
+                        format!("invalid relative path {}", path.components.join("::")),
                     );
-                    return None;
+                    None
                 } else {
                     let mut combined = self.module_path.components
                         [0..self.module_path.components.len() - n]
                         .to_vec();
                     combined.extend_from_slice(&path.components);
-                    DefinitionPath::Absolute(combined)
+                    Some(DefinitionPath::Absolute(combined))
                 }
             }
             syntax::PathOrigin::Package(package) => match self.dependencies.get(package) {
-                Some((package, version)) => DefinitionPath::Package(
+                Some((package, version)) => Some(DefinitionPath::Package(
                     package.clone(),
                     version.clone(),
                     path.components.to_vec(),
-                ),
+                )),
                 None => {
-                    log::warn!("dependency {package} not found");
-                    return None;
+                    self.report(
+                        Lint::UnresolvedPaths,
+                        None,
+                        format!("dependency `{package}` not found"),
+                    );
+                    None
                 }
             },
-        };
-        Some((Ident(name.to_string()), kind, def_path))
+        }
     }
 
-    fn get_decl(&self, target: ResolveTarget) -> Option<(&str, ItemKind, &ModulePath)> {
-        if let Some((decl, kind)) = self.local.get_key_value(target.as_str()) {
-            return Some((decl, *kind, &self.local_path));
+    fn get_decl(&self, target: ResolveTarget) -> Option<(String, ItemKind, &ModulePath)> {
+        if !matches!(target, ResolveTarget::Qualified(..))
+            && let Some((decl, kind)) = self.local.get_key_value(target.as_str())
+        {
+            return Some((decl.clone(), *kind, &self.local_path));
         }
 
-        if let Some(sourcemap) = self.compiled.sourcemap.as_ref() {
-            match target {
-                ResolveTarget::Name(name) => {
-                    // TODO: This assumes the escape mangler was used.
-                    // TODO: This does not work if multiple items with the same name existed before mangling.
-                    let mangler = wesl::EscapeMangler;
-                    let (mangled, kind) = mangled_item(self.compiled, |ident| {
-                        mangler
-                            .unmangle(ident)
-                            .is_some_and(|(_, unmangled)| unmangled == name)
-                    })?;
-
-                    let (path, name) = sourcemap.get_decl(&mangled)?;
-                    return Some((name, kind, path));
+        match target {
+            ResolveTarget::Name(name) => {
+                // Resolve the path through the imports of this module, so that items with the
+                // same name in different modules are told apart.
+                let mut segments = name.split("::").collect::<Vec<_>>();
+                let item = segments.pop()?;
+                let path = match segments.split_first() {
+                    None => self
+                        .imported
+                        .get(item)
+                        .map(|(path, imported_item)| (path.clone(), imported_item.as_str())),
+                    Some((first, rest)) => {
+                        let mut path = match *first {
+                            "package" => ModulePath {
+                                origin: syntax::PathOrigin::Absolute,
+                                components: Vec::new(),
+                            },
+                            "super" => resolve_module_path(
+                                &self.module_path,
+                                &ModulePath {
+                                    origin: syntax::PathOrigin::Relative(1),
+                                    components: Vec::new(),
+                                },
+                            ),
+                            first => match self.imported.get(first) {
+                                Some((path, module)) => {
+                                    let mut path = path.clone();
+                                    path.components.push(module.clone());
+                                    path
+                                }
+                                None if self.dependencies.contains_key(first) => ModulePath {
+                                    origin: syntax::PathOrigin::Package(first.to_string()),
+                                    components: Vec::new(),
+                                },
+                                None => return None,
+                            },
+                        };
+                        path.components
+                            .extend(rest.iter().map(|segment| segment.to_string()));
+                        Some((path, item))
+                    }
+                };
+
+                // Names that are neither declared nor imported by this module are not resolved
+                let (path, item) = path?;
+                self.find_decl(&path, item)
+            }
+            ResolveTarget::MaybeMangled(mangled) => match &self.sources {
+                Sources::Compiled(compiled, _) => {
+                    let sourcemap = compiled.sourcemap.as_ref()?;
+                    let (path, name) = sourcemap.get_decl(mangled)?;
+                    let kind = self
+                        .syntax
+                        .global_declarations
+                        .iter()
+                        .filter_map(|decl| decl_ident_and_kind(decl))
+                        .find(|(ident, _)| ident.name().as_str() == mangled)
+                        .map(|(_, kind)| kind)?;
+                    Some((name.to_string(), kind, path))
                 }
-                ResolveTarget::MaybeMangled(name) => {
-                    let (_, kind) = mangled_item(self.compiled, |ident| ident == name)?;
-                    let (path, name) = sourcemap.get_decl(name)?;
-                    return Some((name, kind, path));
+                // Identifiers are not mangled, so they refer to imported items
+                Sources::Parsed(_) => {
+                    let (path, item) = self.imported.get(mangled)?;
+                    self.find_decl(path, item)
                 }
+            },
+            ResolveTarget::Qualified(path, item) => {
+                // The first segment may refer to an imported module
+                let path = match &path.origin {
+                    syntax::PathOrigin::Package(first)
+                        if !self.dependencies.contains_key(first) =>
+                    {
+                        let (module_path, module) = self.imported.get(first)?;
+                        let mut module_path = module_path.clone();
+                        module_path.components.push(module.clone());
+                        module_path.components.extend_from_slice(&path.components);
+                        module_path
+                    }
+                    _ => resolve_module_path(&self.module_path, path),
+                };
+                self.find_decl(&path, item)
+            }
+        }
+    }
+
+    /// Finds a declaration by its origin in the source map, or by its defining module if the
+    /// module was parsed directly. This does not depend on the mangler that was used.
+    fn find_decl(&self, path: &ModulePath, item: &str) -> Option<(String, ItemKind, &ModulePath)> {
+        match &self.sources {
+            Sources::Compiled(compiled, _) => {
+                let sourcemap = compiled.sourcemap.as_ref()?;
+                self.syntax
+                    .global_declarations
+                    .iter()
+                    .filter_map(|decl| decl_ident_and_kind(decl))
+                    .find_map(|(ident, kind)| {
+                        let (decl_path, name) = sourcemap.get_decl(ident.name().as_str())?;
+                        (name == item && path == decl_path)
+                            .then(|| (name.to_string(), kind, decl_path))
+                    })
             }
+            Sources::Parsed(sources) => sources.find_decl(path, item),
         }
+    }
+}
+
+/// The lint overrides of the item that is being built, see [`Context::enter_item`].
+pub struct ItemScope<'a> {
+    item_lints: &'a RefCell<Option<LintLevels>>,
+}
 
-        None
+impl Drop for ItemScope<'_> {
+    fn drop(&mut self) {
+        *self.item_lints.borrow_mut() = None;
     }
 }
 
@@ -245,6 +449,9 @@ pub enum ResolveTarget<'a> {
     Name(&'a str),
     /// Identifier from the source code.
     MaybeMangled(&'a str),
+    /// Identifier with an inline module path from the source code, e.g. `package::a::Item`.
+    /// Only occurs in modules that were parsed directly.
+    Qualified(&'a ModulePath, &'a str),
 }
 
 impl ResolveTarget<'_> {
@@ -252,79 +459,107 @@ impl ResolveTarget<'_> {
         match self {
             ResolveTarget::Name(name) => name,
             ResolveTarget::MaybeMangled(name) => name,
+            ResolveTarget::Qualified(_, name) => name,
         }
     }
 }
 
-fn mangled_item(
-    compiled: &CompileResult,
-    mut f: impl FnMut(&str) -> bool,
-) -> Option<(String, ItemKind)> {
-    for decl in &compiled.syntax.global_declarations {
-        let Some(ident) = decl.ident() else {
-            continue;
-        };
-        if f(ident.name().as_str()) {
-            return match decl.node() {
-                syntax::GlobalDeclaration::Void => None,
-                syntax::GlobalDeclaration::Compound(_) => {
-                    panic!("compound should have been flattened")
-                }
-                syntax::GlobalDeclaration::Declaration(declaration) => match declaration.kind {
-                    syntax::DeclarationKind::Const => Some(ItemKind::Constant),
-                    syntax::DeclarationKind::Override => None,
-                    syntax::DeclarationKind::Let => None, // should be unreachable?
-                    syntax::DeclarationKind::Var(_) => Some(ItemKind::GlobalVariable),
-                },
-                syntax::GlobalDeclaration::TypeAlias(_) => Some(ItemKind::TypeAlias),
-                syntax::GlobalDeclaration::Struct(_) => Some(ItemKind::Struct),
-                syntax::GlobalDeclaration::Function(_) => Some(ItemKind::Function),
-                syntax::GlobalDeclaration::ConstAssert(_const_assert) => None,
-            }
-            .map(|kind| (ident.name().to_string(), kind));
+pub fn decl_ident_and_kind(decl: &syntax::GlobalDeclaration) -> Option<(&syntax::Ident, ItemKind)> {
+    match decl {
+        syntax::GlobalDeclaration::Void => None,
+        syntax::GlobalDeclaration::Compound(_) => None,
+        syntax::GlobalDeclaration::Declaration(declaration) => match declaration.kind {
+            syntax::DeclarationKind::Const => Some((&declaration.ident, ItemKind::Constant)),
+            syntax::DeclarationKind::Override => Some((&declaration.ident, ItemKind::Override)),
+            syntax::DeclarationKind::Let => None, // should be unreachable?
+            syntax::DeclarationKind::Var(_) => Some((&declaration.ident, ItemKind::GlobalVariable)),
+        },
+        syntax::GlobalDeclaration::TypeAlias(type_alias) => {
+            Some((&type_alias.ident, ItemKind::TypeAlias))
+        }
+        syntax::GlobalDeclaration::Struct(struct_) => Some((&struct_.ident, ItemKind::Struct)),
+        syntax::GlobalDeclaration::Function(function) => {
+            Some((&function.ident, ItemKind::Function))
         }
+        syntax::GlobalDeclaration::ConstAssert(_const_assert) => None,
     }
+}
 
-    None
+pub fn decl_attributes(decl: &syntax::GlobalDeclaration) -> &[syntax::AttributeNode] {
+    match decl {
+        syntax::GlobalDeclaration::Void => &[],
+        syntax::GlobalDeclaration::Compound(_) => &[],
+        syntax::GlobalDeclaration::Declaration(declaration) => &declaration.attributes,
+        syntax::GlobalDeclaration::TypeAlias(type_alias) => &type_alias.attributes,
+        syntax::GlobalDeclaration::Struct(struct_) => &struct_.attributes,
+        syntax::GlobalDeclaration::Function(function) => &function.attributes,
+        syntax::GlobalDeclaration::ConstAssert(const_assert) => &const_assert.attributes,
+    }
 }
 
-// TODO: This assumes the re-exported items are defined in the re-exported module.
-// This does not handle re-exports of re-exports correctly.
-fn collect_exports(imports: &[syntax::ImportStatement]) -> HashMap<(ModulePath, Ident), Ident> {
+/// Flattens import statements into `(statement index, path, item, rename)`. The paths are
+/// resolved relative to `module_path`.
+pub fn flatten_imports(
+    module_path: &ModulePath,
+    imports: &[syntax::ImportStatement],
+) -> Vec<(usize, ModulePath, Ident, Ident)> {
     fn add_rec(
-        exports: &mut HashMap<(ModulePath, Ident), Ident>,
+        flattened: &mut Vec<(usize, ModulePath, Ident, Ident)>,
+        idx: usize,
         path: &ModulePath,
         content: &syntax::ImportContent,
     ) {
         match content {
             syntax::ImportContent::Item(import_item) => {
-                exports.insert(
-                    (path.clone(), map(&import_item.ident)),
-                    map(&import_item
-                        .rename
-                        .clone()
-                        .unwrap_or_else(|| import_item.ident.clone())),
-                );
+                flattened.push((
+                    idx,
+                    path.clone(),
+                    map(&import_item.ident),
+                    map(import_item.rename.as_ref().unwrap_or(&import_item.ident)),
+                ));
             }
             syntax::ImportContent::Collection(imports) => {
-                for import in imports {
+                for inner in imports {
                     let mut path = path.clone();
-                    path.components.extend(import.path.iter().cloned());
-                    add_rec(exports, &path, &import.content);
+                    path.components.extend(inner.path.iter().cloned());
+                    add_rec(flattened, idx, &path, &inner.content);
                 }
             }
         }
     }
 
-    let mut exports = HashMap::new();
-    for import in imports {
-        let is_export = import
-            .attributes
-            .iter()
-            .any(|attr| **attr == syntax::Attribute::Publish);
-        if is_export && let Some(path) = &import.path {
-            add_rec(&mut exports, path, &import.content);
+    let mut flattened = Vec::new();
+    for (idx, import) in imports.iter().enumerate() {
+        if let Some(path) = &import.path {
+            let path = resolve_module_path(module_path, path);
+            add_rec(&mut flattened, idx, &path, &import.content);
+        }
+    }
+    flattened
+}
+
+/// Resolves an import path relative to the module it appears in.
+pub fn resolve_module_path(module_path: &ModulePath, path: &ModulePath) -> ModulePath {
+    match &path.origin {
+        syntax::PathOrigin::Absolute => ModulePath {
+            // `package::` inside a dependency refers to the root of that dependency.
+            origin: match &module_path.origin {
+                syntax::PathOrigin::Package(package) => {
+                    syntax::PathOrigin::Package(package.clone())
+                }
+                _ => syntax::PathOrigin::Absolute,
+            },
+            components: path.components.clone(),
+        },
+        syntax::PathOrigin::Relative(n) => {
+            let keep = module_path.components.len().saturating_sub(*n);
+            let mut components = module_path.components[..keep].to_vec();
+            components.extend_from_slice(&path.components);
+            ModulePath {
+                origin: module_path.origin.clone(),
+                components,
+            }
         }
+        syntax::PathOrigin::Package(_) => path.clone(),
     }
-    exports
 }
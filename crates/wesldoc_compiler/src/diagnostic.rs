@@ -0,0 +1,137 @@
+use crate::LintLevel;
+use std::{fmt, ops::Range};
+use wesldoc_ast::{Module, WeslDocs};
+
+/// An issue found while compiling the docs of a package.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub lint: Lint,
+    /// Path of the module the issue was found in, relative to the package root.
+    pub module_path: Vec<String>,
+    /// Byte range in the source of the module, if the issue can be located.
+    pub span: Option<Range<usize>>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Creates a report with the module source attached, for rendering with `miette`.
+    pub fn to_report(&self, docs: &WeslDocs) -> miette::Report {
+        let severity = match self.severity {
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Error => miette::Severity::Error,
+        };
+        let labels = self
+            .span
+            .iter()
+            .map(|span| miette::LabeledSpan::at(span.clone(), self.lint.name()))
+            .collect::<Vec<_>>();
+        let report = miette::miette!(
+            severity = severity,
+            labels = labels,
+            "{} (in module `{}`)",
+            self.message,
+            self.module_path.join("::")
+        );
+
+        match find_module(&docs.root, &self.module_path).and_then(|m| m.source.clone()) {
+            Some(source) => report.with_source_code(source),
+            None => report,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {} (in module `{}`)",
+            self.severity,
+            self.lint.name(),
+            self.message,
+            self.module_path.join("::")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// A module or item has no doc comment.
+    MissingDocs,
+    /// An intra-doc link could not be resolved.
+    BrokenIntraDocLinks,
+    /// A `@publish` import could not be resolved.
+    UnresolvedReExports,
+    /// A module path refers to a missing module or dependency.
+    UnresolvedPaths,
+    /// The module was compiled without a source map, so sources and links are missing.
+    MissingSourceMap,
+    /// The module contains syntax that can't be documented.
+    UnsupportedSyntax,
+    /// A lint level refers to a lint that doesn't exist.
+    UnknownLints,
+    /// A doc comment tag such as `@since` has an invalid value.
+    InvalidDocTags,
+}
+
+impl Lint {
+    pub const ALL: &[Lint] = &[
+        Lint::MissingDocs,
+        Lint::BrokenIntraDocLinks,
+        Lint::UnresolvedReExports,
+        Lint::UnresolvedPaths,
+        Lint::MissingSourceMap,
+        Lint::UnsupportedSyntax,
+        Lint::UnknownLints,
+        Lint::InvalidDocTags,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+
+    pub fn default_level(self) -> LintLevel {
+        match self {
+            Lint::MissingDocs => LintLevel::Allow,
+            _ => LintLevel::Warn,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::MissingDocs => "missing_docs",
+            Lint::BrokenIntraDocLinks => "broken_intra_doc_links",
+            Lint::UnresolvedReExports => "unresolved_re_exports",
+            Lint::UnresolvedPaths => "unresolved_paths",
+            Lint::MissingSourceMap => "missing_source_map",
+            Lint::UnsupportedSyntax => "unsupported_syntax",
+            Lint::UnknownLints => "unknown_lints",
+            Lint::InvalidDocTags => "invalid_doc_tags",
+        }
+    }
+}
+
+fn find_module<'a>(root: &'a Module, path: &[String]) -> Option<&'a Module> {
+    path.iter().try_fold(root, |module, name| {
+        module.modules.iter().find(|m| m.name == *name)
+    })
+}
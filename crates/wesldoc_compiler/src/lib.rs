@@ -1,131 +1,296 @@
 mod build_attributes;
 mod build_conditional;
+mod build_directives;
 mod build_doc_comment;
 mod build_expression;
+mod build_re_exports;
 mod build_type;
 mod calculate_span;
 mod collect_features;
 mod compile_state;
 mod context;
+mod diagnostic;
 mod extract_comments;
+mod lints;
 mod map;
+mod parsed;
 mod post_process;
 
 use self::{
     build_attributes::build_attributes,
     build_conditional::{ConditionalScope, build_conditional},
+    build_directives::build_directives,
     build_doc_comment::{build_inner_doc_comment, build_outer_doc_comment},
     build_expression::build_expression,
+    build_re_exports::build_re_exports,
     build_type::build_type,
     calculate_span::calculate_span,
     collect_features::collect_features,
-    compile_state::{CompileState, CompileStats},
-    context::{Context, ResolveTarget},
+    compile_state::CompileState,
+    context::{Context, ResolveTarget, decl_attributes},
     extract_comments::{extract_comments_inner, extract_comments_outer},
     map::map,
+    parsed::ParsedSources,
 };
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::{borrow::Cow, collections::HashMap, ops::Range};
 use thiserror::Error;
 use wesl::{CompileResult, ModulePath, syntax};
 use wesldoc_ast::*;
 
-pub type Result<T, E = Error> = std::result::Result<T, E>;
+pub use self::{
+    diagnostic::{Diagnostic, Lint, Severity},
+    lints::{LintLevel, LintLevels},
+    parsed::{ParsedModule, ParsedPackage, ParsedSource},
+};
 
+/// An error that stops documenting a module. The module is still documented with its source and
+/// the error, all other modules are unaffected.
 #[derive(Debug, Error)]
-pub enum Error {
-    #[error("package has missing documentation")]
-    MissingDocumentation,
+enum FatalError {
+    #[error("unsupported syntax: {message}")]
+    UnsupportedSyntax {
+        message: &'static str,
+        range: Range<usize>,
+    },
 }
 
-impl From<FatalError> for Error {
-    fn from(e: FatalError) -> Self {
-        match e {}
+impl FatalError {
+    fn range(&self) -> Range<usize> {
+        match self {
+            FatalError::UnsupportedSyntax { range, .. } => range.clone(),
+        }
     }
 }
 
-#[derive(Debug, Error)]
-enum FatalError {}
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum MissingDocumentation {
-    #[default]
-    Allow,
-    Warn,
-    Deny,
-}
-
 #[derive(Debug, Default, Clone)]
 pub struct CompileOptions {
-    pub missing_documentation: MissingDocumentation,
+    /// Lint levels of the package. They can be overridden per module and item in the sources.
+    pub lints: LintLevels,
 }
 
 pub struct WeslPackage {
     pub version: Version,
     pub dependencies: HashMap<String, (String, Version)>,
     pub root: WeslModule,
+    /// Modules that were resolved while compiling, keyed by their resolved path. Their syntax
+    /// trees are used instead of parsing the modules again, e.g. to follow re-exports.
+    pub sources: IndexMap<ModulePath, ParsedSource>,
 }
 
 pub struct WeslModule {
     pub name: String,
     pub compiled: Option<(Vec<syntax::ImportStatement>, CompileResult)>,
+    pub error: Option<WeslModuleError>,
     pub submodules: Vec<WeslModule>,
 }
 
-pub fn compile(
-    package: &WeslPackage,
-    options: &CompileOptions,
-) -> Result<(WeslDocs, CompileStats)> {
+/// A module that failed to compile. It is still documented with its raw source and the error.
+pub struct WeslModuleError {
+    pub message: String,
+    pub source: Option<String>,
+}
+
+/// The result of compiling a package.
+pub struct CompileOutput {
+    pub docs: WeslDocs,
+    pub coverage: Coverage,
+    /// All errors and warnings, in the order they were found.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CompileOutput {
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.is_error()).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics.iter().filter(|d| !d.is_error()).count()
+    }
+}
+
+pub fn compile(package: &WeslPackage, options: &CompileOptions) -> CompileOutput {
+    let sources = ParsedSources::from_sources(&package.sources);
+
     let compile_state = CompileState::default();
     let mut docs = WeslDocs {
         version: package.version.clone(),
         root: compile_module(
             &package.root,
             &[],
+            &sources,
+            &package.dependencies,
+            &options.lints,
+            &compile_state,
+        ),
+    };
+    let diagnostics = compile_state.finish();
+
+    post_process::post_process(&mut docs);
+
+    CompileOutput {
+        coverage: Coverage::of(&docs),
+        docs,
+        diagnostics,
+    }
+}
+
+/// Compiles a package from directly parsed modules, for pipelines that don't produce source
+/// maps. Imports are resolved by wesldoc, so items of dependencies are only linked if their
+/// modules were loaded with [`ParsedPackage::load_dependencies`]. This skips the `wesl` compiler
+/// entirely, which is also much faster for packages with large dependency graphs.
+pub fn compile_parsed(package: &ParsedPackage, options: &CompileOptions) -> CompileOutput {
+    let sources = ParsedSources::new(&package.root, &package.dependency_sources);
+
+    let compile_state = CompileState::default();
+    let mut docs = WeslDocs {
+        version: package.version.clone(),
+        root: compile_parsed_module(
+            &package.root,
+            &[],
+            &sources,
             &package.dependencies,
-            options,
+            &options.lints,
             &compile_state,
-        )?,
+        ),
     };
-    let compile_stats = compile_state.into_result()?;
+    let diagnostics = compile_state.finish();
 
     post_process::post_process(&mut docs);
 
-    Ok((docs, compile_stats))
+    CompileOutput {
+        coverage: Coverage::of(&docs),
+        docs,
+        diagnostics,
+    }
 }
 
 fn compile_module(
     wesl_module: &WeslModule,
     path: &[String],
+    sources: &ParsedSources,
     dependencies: &HashMap<String, (String, Version)>,
-    compile_options: &CompileOptions,
+    lints: &LintLevels,
     compile_state: &CompileState,
-) -> Result<Module, FatalError> {
+) -> Module {
     let mut module = Module::empty(wesl_module.name.clone());
-    module.modules = wesl_module
-        .submodules
-        .iter()
-        .map(|m| {
+
+    // Lint levels of this module are inherited by its submodules
+    let mut lints = Cow::Borrowed(lints);
+    if let Some(error) = &wesl_module.error {
+        module.source = error.source.clone();
+        module.error = Some(error.message.clone());
+    } else if let Some((imports, compiled)) = &wesl_module.compiled {
+        let ctx = Context::init(
+            imports,
+            compiled,
+            sources,
+            ModulePath {
+                origin: syntax::PathOrigin::Absolute,
+                components: path.to_vec(),
+            },
+            dependencies,
+            &lints,
+            compile_state,
+        );
+        build_module_or_error(&mut module, &ctx);
+        lints = Cow::Owned(ctx.lints().clone());
+    }
+
+    module.modules = compile_submodules(
+        &wesl_module.submodules,
+        compile_state,
+        |m, compile_state| {
             let mut path = path.to_vec();
             path.push(m.name.clone());
-            compile_module(m, &path, dependencies, compile_options, compile_state)
-        })
-        .collect::<Result<Vec<_>, FatalError>>()?;
-
-    let Some((imports, compiled)) = &wesl_module.compiled else {
-        return Ok(module);
-    };
-    let ctx = Context::init(
-        imports,
-        compiled,
-        ModulePath {
-            origin: syntax::PathOrigin::Absolute,
-            components: path.to_vec(),
+            compile_module(m, &path, sources, dependencies, &lints, compile_state)
         },
-        dependencies,
-        compile_options,
+    );
+
+    module
+}
+
+fn compile_parsed_module(
+    parsed_module: &ParsedModule,
+    path: &[String],
+    sources: &ParsedSources,
+    dependencies: &HashMap<String, (String, Version)>,
+    lints: &LintLevels,
+    compile_state: &CompileState,
+) -> Module {
+    let mut module = Module::empty(parsed_module.name.clone());
+
+    // Lint levels of this module are inherited by its submodules
+    let mut lints = Cow::Borrowed(lints);
+    if let Some(error) = &parsed_module.error {
+        module.source = error.source.clone();
+        module.error = Some(error.message.clone());
+    } else if let Some(parsed) = &parsed_module.parsed {
+        let ctx = Context::init_parsed(
+            &parsed.syntax,
+            sources,
+            ModulePath {
+                origin: syntax::PathOrigin::Absolute,
+                components: path.to_vec(),
+            },
+            dependencies,
+            &lints,
+            compile_state,
+        );
+        build_module_or_error(&mut module, &ctx);
+        lints = Cow::Owned(ctx.lints().clone());
+    }
+
+    module.modules = compile_submodules(
+        &parsed_module.submodules,
         compile_state,
+        |m, compile_state| {
+            let mut path = path.to_vec();
+            path.push(m.name.clone());
+            compile_parsed_module(m, &path, sources, dependencies, &lints, compile_state)
+        },
     );
 
+    module
+}
+
+/// Compiles the submodules of a module concurrently. Each submodule reports into its own state,
+/// which is merged in order, so diagnostics are the same as when compiling sequentially.
+fn compile_submodules<M: Sync>(
+    submodules: &[M],
+    compile_state: &CompileState,
+    compile: impl Fn(&M, &CompileState) -> Module + Sync,
+) -> Vec<Module> {
+    submodules
+        .par_iter()
+        .map(|m| {
+            let state = CompileState::default();
+            (compile(m, &state), state)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|(module, state)| {
+            compile_state.merge(state);
+            module
+        })
+        .collect()
+}
+
+/// Builds the module from its context. If the module can't be documented, only its source and the
+/// error are kept.
+fn build_module_or_error(module: &mut Module, ctx: &Context) {
+    let mut built = Module::empty(module.name.clone());
+    match build_module(&mut built, ctx) {
+        Ok(()) => *module = built,
+        Err(err) => {
+            report_fatal_error(&err, ctx);
+            module.source = ctx.get_source().map(|source| source.to_string());
+            module.error = Some(err.to_string());
+        }
+    }
+}
+
+fn build_module(module: &mut Module, ctx: &Context) -> Result<(), FatalError> {
     // Set source
     if let Some(source) = ctx.get_source() {
         module.source = Some(source.to_string());
@@ -135,31 +300,65 @@ fn compile_module(
     module.comment = module
         .source
         .as_ref()
-        .and_then(|source| build_inner_doc_comment(&extract_comments_inner(source), &ctx));
-    validate_module_doc_comment(&module, &ctx);
+        .and_then(|source| build_inner_doc_comment(&extract_comments_inner(source), ctx));
+    validate_module_doc_comment(module, ctx);
 
     // Collect translate time features
-    module.translate_time_features = collect_features(&ctx);
+    module.translate_time_features = collect_features(ctx);
 
-    // Compile locally defined global declarations and re-exports
+    // Collect global directives and the extensions they require
+    module.directives = build_directives(ctx);
+    module.required_extensions = module
+        .directives
+        .iter()
+        .flat_map(|directive| directive.kind.extensions().iter().cloned())
+        .collect();
+
+    // Collect re-exports
+    module.re_exports = build_re_exports(ctx);
+
+    // Const assertions have no name to tell the ones of imported modules apart, so they are taken
+    // from the module's own syntax tree
     let mut conditional_scope = ConditionalScope::new();
-    for decl in &compiled.syntax.global_declarations {
-        let export_ctx;
-        let mut export_conditional_scope;
-        let (name, ctx, conditional_scope) = if let Some(name) = ctx.as_local(decl) {
-            (name, &ctx, &mut conditional_scope)
-        } else if let Some((module_path, name)) = ctx.as_export(decl) {
-            // TODO: In the html output the source link is broken for re-exports.
-            // It points to this module, instead of the module where the item is originally defined.
-
-            // TODO: The empty conditional scope created below is not correct.
-            // It should come from the module where the item is originally defined. For this we
-            // need to compile/resolve that module too?
-
-            export_ctx = ctx.at_path(module_path);
-            export_conditional_scope = ConditionalScope::new();
-            (name.clone(), &export_ctx, &mut export_conditional_scope)
-        } else {
+    for decl in &ctx.own_syntax().global_declarations {
+        let conditional = build_conditional(&mut conditional_scope, decl_attributes(decl.node()));
+        let syntax::GlobalDeclaration::ConstAssert(const_assert) = decl.node() else {
+            continue;
+        };
+        let _item = ctx.enter_item(&const_assert.attributes);
+        let span = calculate_span(decl.span().range(), ctx);
+        module.const_asserts.push(ConstAssert {
+            expression: ctx
+                .get_source()
+                .and_then(|source| source.get(const_assert.expression.span().range()))
+                .map(|expression| expression.to_string())
+                .unwrap_or_else(|| const_assert.expression.node().to_string()),
+            conditional,
+            comment: span
+                .and_then(|span| Some((span, ctx.get_source()?)))
+                .and_then(|(span, source)| {
+                    build_outer_doc_comment(&extract_comments_outer(span, source), ctx)
+                }),
+            span,
+        });
+    }
+
+    // Compile locally defined global declarations
+    let mut conditional_scope = ConditionalScope::new();
+    for decl in &ctx.syntax().global_declarations {
+        // Lint overrides apply until the next declaration, also when it is skipped
+        let _item = ctx.enter_item(decl_attributes(decl.node()));
+        if let syntax::GlobalDeclaration::ConstAssert(_) = decl.node() {
+            continue;
+        }
+        if let syntax::GlobalDeclaration::Compound(_) = decl.node() {
+            return Err(FatalError::UnsupportedSyntax {
+                message: "compound declarations are not supported",
+                range: decl.span().range(),
+            });
+        }
+
+        let Some(name) = ctx.as_local(decl) else {
             continue;
         };
 
@@ -173,9 +372,7 @@ fn compile_module(
 
         match decl.node() {
             syntax::GlobalDeclaration::Void => (),
-            syntax::GlobalDeclaration::Compound(_) => {
-                panic!("compound should have been flattened")
-            }
+            syntax::GlobalDeclaration::Compound(_) => (), // Rejected above
             syntax::GlobalDeclaration::Declaration(declaration) => match declaration.kind {
                 syntax::DeclarationKind::Const => {
                     module
@@ -193,7 +390,7 @@ fn compile_module(
                                 .unwrap_or(Expression::NotExpanded(None)),
                             attributes: build_attributes(&declaration.attributes, ctx),
                             conditional: build_conditional(
-                                conditional_scope,
+                                &mut conditional_scope,
                                 &declaration.attributes,
                             ),
                             comment,
@@ -215,7 +412,7 @@ fn compile_module(
                                 .map(|expr| build_expression(expr, ctx)),
                             attributes: build_attributes(&declaration.attributes, ctx),
                             conditional: build_conditional(
-                                conditional_scope,
+                                &mut conditional_scope,
                                 &declaration.attributes,
                             ),
                             comment,
@@ -241,7 +438,7 @@ fn compile_module(
                                 .map(|expr| build_expression(expr, ctx)),
                             attributes: build_attributes(&declaration.attributes, ctx),
                             conditional: build_conditional(
-                                conditional_scope,
+                                &mut conditional_scope,
                                 &declaration.attributes,
                             ),
                             comment,
@@ -259,7 +456,10 @@ fn compile_module(
                         name,
                         ty: build_type(&type_alias.ty, ctx),
                         attributes: build_attributes(&type_alias.attributes, ctx),
-                        conditional: build_conditional(conditional_scope, &type_alias.attributes),
+                        conditional: build_conditional(
+                            &mut conditional_scope,
+                            &type_alias.attributes,
+                        ),
                         comment,
                         span,
                     });
@@ -301,7 +501,7 @@ fn compile_module(
                                 .collect()
                         },
                         attributes: build_attributes(&struct_.attributes, ctx),
-                        conditional: build_conditional(conditional_scope, &struct_.attributes),
+                        conditional: build_conditional(&mut conditional_scope, &struct_.attributes),
                         comment,
                         span,
                     });
@@ -336,7 +536,10 @@ fn compile_module(
                             .map(|ret| build_type(ret, ctx)),
                         attributes: build_attributes(&function.attributes, ctx),
                         return_attributes: build_attributes(&function.return_attributes, ctx),
-                        conditional: build_conditional(conditional_scope, &function.attributes),
+                        conditional: build_conditional(
+                            &mut conditional_scope,
+                            &function.attributes,
+                        ),
                         comment,
                         span,
                     });
@@ -345,52 +548,24 @@ fn compile_module(
         }
     }
 
-    Ok(module)
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Severity {
-    Warn,
-    Error,
+    Ok(())
 }
 
-impl Severity {
-    fn to_miette_severity(self) -> miette::Severity {
-        match self {
-            Severity::Warn => miette::Severity::Warning,
-            Severity::Error => miette::Severity::Error,
-        }
-    }
+fn report_fatal_error(error: &FatalError, ctx: &Context) {
+    ctx.report(
+        Lint::UnsupportedSyntax,
+        Some(error.range()),
+        format!("module could not be documented, {error}"),
+    );
 }
 
 fn validate_module_doc_comment(module: &Module, ctx: &Context) {
-    let is_documented = module.comment.is_some();
-    ctx.compile_state().track_documented(is_documented);
-    if is_documented {
-        return;
-    }
-    let severity = match ctx.compile_options().missing_documentation {
-        MissingDocumentation::Allow => return,
-        MissingDocumentation::Warn => Severity::Warn,
-        MissingDocumentation::Deny => Severity::Error,
-    };
-    let mut report = miette::miette!(
-        severity = severity.to_miette_severity(),
-        "missing module documentation for module `{}`",
-        module.name
-    );
-    if let Some(source) = ctx.get_source() {
-        report = report.with_source_code(source.to_string());
-    }
-    match severity {
-        Severity::Warn => {
-            log::warn!("{report:?}");
-        }
-        Severity::Error => {
-            log::error!("{report:?}");
-            ctx.compile_state()
-                .report_error(Error::MissingDocumentation);
-        }
+    if module.comment.is_none() {
+        ctx.report(
+            Lint::MissingDocs,
+            None,
+            format!("missing module documentation for module `{}`", module.name),
+        );
     }
 }
 
@@ -399,35 +574,11 @@ fn validate_item_doc_comment(
     span: wesl::syntax::Span,
     ctx: &Context,
 ) {
-    let is_documented = comment.is_some();
-    ctx.compile_state().track_documented(is_documented);
-    if is_documented {
-        return;
-    }
-    let severity = match ctx.compile_options().missing_documentation {
-        MissingDocumentation::Allow => return,
-        MissingDocumentation::Warn => Severity::Warn,
-        MissingDocumentation::Deny => Severity::Error,
-    };
-    let mut report = miette::miette!(
-        labels = vec![miette::LabeledSpan::at(
-            span.range(),
-            "missing documentation"
-        )],
-        severity = severity.to_miette_severity(),
-        "missing item documentation"
-    );
-    if let Some(source) = ctx.get_source() {
-        report = report.with_source_code(source.to_string());
-    }
-    match severity {
-        Severity::Warn => {
-            log::warn!("{report:?}");
-        }
-        Severity::Error => {
-            log::error!("{report:?}");
-            ctx.compile_state()
-                .report_error(Error::MissingDocumentation);
-        }
+    if comment.is_none() {
+        ctx.report(
+            Lint::MissingDocs,
+            Some(span.range()),
+            "missing item documentation",
+        );
     }
 }
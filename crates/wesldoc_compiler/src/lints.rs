@@ -0,0 +1,114 @@
+use crate::{Lint, Severity, extract_comments::extract_comments_inner};
+use std::collections::HashMap;
+use wesl::syntax;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    pub fn from_name(name: &str) -> Option<LintLevel> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(Severity::Warning),
+            LintLevel::Deny => Some(Severity::Error),
+        }
+    }
+}
+
+/// Levels of all lints. Lints without an explicit level use [`Lint::default_level`].
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn get(&self, lint: Lint) -> LintLevel {
+        self.levels
+            .get(&lint)
+            .copied()
+            .unwrap_or_else(|| lint.default_level())
+    }
+}
+
+/// A lint level set in a source file. Unknown levels or lints are kept as an error message.
+pub type LintOverride = Result<(LintLevel, Lint), String>;
+
+/// Parses `//! @lint(level, lint, ...)` lines of the inner doc comment of a module.
+pub fn module_lint_overrides(source: &str) -> Vec<LintOverride> {
+    extract_comments_inner(source)
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start().strip_prefix("//!")?.trim();
+            let args = line.strip_prefix("@lint(")?.strip_suffix(')')?;
+            let (level, lints) = args.split_once(',').unwrap_or((args, ""));
+            Some(parse_lints(level, lints))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Parses `@wesldoc(level(lint, ...), ...)` attributes of an item.
+pub fn item_lint_overrides(attributes: &[syntax::AttributeNode]) -> Vec<LintOverride> {
+    attributes
+        .iter()
+        .filter_map(|attr| match attr.node() {
+            syntax::Attribute::Custom(custom) if custom.name == "wesldoc" => {
+                custom.arguments.as_ref()
+            }
+            _ => None,
+        })
+        .flatten()
+        .flat_map(|arg| {
+            let arg = arg.node().to_string();
+            match arg.split_once('(') {
+                Some((level, lints)) => match lints.strip_suffix(')') {
+                    Some(lints) => parse_lints(level, lints),
+                    None => vec![Err(format!("invalid lint level `{arg}`"))],
+                },
+                None => vec![Err(format!("invalid lint level `{arg}`"))],
+            }
+        })
+        .collect()
+}
+
+/// Checks if an attribute only controls wesldoc and should not be rendered.
+pub fn is_wesldoc_attribute(attr: &syntax::Attribute) -> bool {
+    matches!(attr, syntax::Attribute::Custom(custom) if custom.name == "wesldoc")
+}
+
+/// Checks if a line of a module doc comment sets lint levels. These lines are not rendered.
+pub fn is_lint_line(line: &str) -> bool {
+    line.trim().starts_with("@lint(")
+}
+
+fn parse_lints(level: &str, lints: &str) -> Vec<LintOverride> {
+    let Some(level) = LintLevel::from_name(level.trim()) else {
+        return vec![Err(format!("unknown lint level `{}`", level.trim()))];
+    };
+    lints
+        .split(',')
+        .map(str::trim)
+        .filter(|lint| !lint.is_empty())
+        .map(|lint| match Lint::from_name(lint) {
+            Some(lint) => Ok((level, lint)),
+            None => Err(format!("unknown lint `{lint}`")),
+        })
+        .collect()
+}
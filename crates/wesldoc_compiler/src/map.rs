@@ -17,33 +17,16 @@ impl Map<Ident> for syntax::Ident {
 
 impl Map<AddressSpace> for (syntax::AddressSpace, Option<syntax::AccessMode>) {
     fn map(&self) -> AddressSpace {
-        // TODO: How to handle atomic?
-
         let (address_space, access_mode) = self;
         match address_space {
             syntax::AddressSpace::Function => AddressSpace::Function,
             syntax::AddressSpace::Private => AddressSpace::Private,
             syntax::AddressSpace::Workgroup => AddressSpace::WorkGroup,
             syntax::AddressSpace::Uniform => AddressSpace::Uniform,
-            syntax::AddressSpace::Storage => match access_mode {
-                Some(syntax::AccessMode::Read) => AddressSpace::Storage {
-                    load: true,
-                    store: false,
-                },
-                Some(syntax::AccessMode::Write) => AddressSpace::Storage {
-                    load: false,
-                    store: true,
-                },
-                Some(syntax::AccessMode::ReadWrite) => AddressSpace::Storage {
-                    load: true,
-                    store: true,
-                },
-                Some(syntax::AccessMode::Atomic) => unreachable!(), // TODO: ???
-                None => AddressSpace::Storage {
-                    load: true,
-                    store: false,
-                },
-            },
+            // The default access mode of the storage address space is `read`
+            syntax::AddressSpace::Storage => {
+                AddressSpace::Storage(access_mode.as_ref().map_or(AccessMode::Read, map))
+            }
             syntax::AddressSpace::Handle => AddressSpace::Handle,
             syntax::AddressSpace::Immediate => AddressSpace::Immediate,
             syntax::AddressSpace::TaskPayload => AddressSpace::TaskPayload,
@@ -51,6 +34,17 @@ impl Map<AddressSpace> for (syntax::AddressSpace, Option<syntax::AccessMode>) {
     }
 }
 
+impl Map<AccessMode> for syntax::AccessMode {
+    fn map(&self) -> AccessMode {
+        match self {
+            syntax::AccessMode::Read => AccessMode::Read,
+            syntax::AccessMode::Write => AccessMode::Write,
+            syntax::AccessMode::ReadWrite => AccessMode::ReadWrite,
+            syntax::AccessMode::Atomic => AccessMode::Atomic,
+        }
+    }
+}
+
 impl Map<Literal> for syntax::LiteralExpression {
     fn map(&self) -> Literal {
         match *self {
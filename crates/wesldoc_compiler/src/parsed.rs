@@ -0,0 +1,210 @@
+use crate::{
+    WeslModuleError,
+    build_re_exports::MAX_RE_EXPORT_DEPTH,
+    context::{decl_ident_and_kind, flatten_imports},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use wesl::{ModulePath, Resolver, syntax};
+use wesldoc_ast::{IndexMap, ItemKind, Version};
+
+/// A package whose modules were parsed directly instead of being compiled by `wesl`. See
+/// [`compile_parsed`](crate::compile_parsed).
+pub struct ParsedPackage {
+    pub version: Version,
+    pub dependencies: HashMap<String, (String, Version)>,
+    pub root: ParsedModule,
+    /// Modules of dependencies that are imported by the package, keyed by their package path.
+    /// See [`ParsedPackage::load_dependencies`].
+    pub dependency_sources: IndexMap<ModulePath, ParsedSource>,
+}
+
+impl ParsedPackage {
+    /// Loads the modules of dependencies that are imported by the package, directly or through
+    /// other dependency modules. Modules that cannot be resolved or parsed are skipped, references
+    /// to them stay unresolved.
+    pub fn load_dependencies(&mut self, resolver: &impl Resolver) {
+        self.dependency_sources = load_dependencies(&self.root, resolver);
+    }
+}
+
+pub struct ParsedModule {
+    pub name: String,
+    pub parsed: Option<ParsedSource>,
+    pub error: Option<WeslModuleError>,
+    pub submodules: Vec<ParsedModule>,
+}
+
+/// The source of a module together with its syntax tree.
+pub struct ParsedSource {
+    pub source: String,
+    pub syntax: syntax::TranslationUnit,
+}
+
+/// Sources and syntax trees of the modules of a package and the dependency modules they import,
+/// keyed by their resolved path.
+pub struct ParsedSources<'a> {
+    modules: IndexMap<ModulePath, (&'a str, &'a syntax::TranslationUnit)>,
+}
+
+impl<'a> ParsedSources<'a> {
+    pub fn new(
+        root: &'a ParsedModule,
+        dependencies: &'a IndexMap<ModulePath, ParsedSource>,
+    ) -> Self {
+        fn add_rec<'a>(
+            modules: &mut IndexMap<ModulePath, (&'a str, &'a syntax::TranslationUnit)>,
+            module: &'a ParsedModule,
+            path: &ModulePath,
+        ) {
+            if let Some(parsed) = &module.parsed {
+                modules.insert(path.clone(), (&parsed.source, &parsed.syntax));
+            }
+            for submodule in &module.submodules {
+                let mut path = path.clone();
+                path.components.push(submodule.name.clone());
+                add_rec(modules, submodule, &path);
+            }
+        }
+
+        let mut modules = IndexMap::new();
+        add_rec(&mut modules, root, &root_path());
+        for (path, parsed) in dependencies {
+            modules.insert(path.clone(), (&parsed.source, &parsed.syntax));
+        }
+
+        Self { modules }
+    }
+
+    /// Sources of modules that were already resolved, e.g. by `wesl` while compiling.
+    pub fn from_sources(sources: &'a IndexMap<ModulePath, ParsedSource>) -> Self {
+        Self {
+            modules: sources
+                .iter()
+                .map(|(path, parsed)| (path.clone(), (parsed.source.as_str(), &parsed.syntax)))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, path: &ModulePath) -> Option<(&'a str, &'a syntax::TranslationUnit)> {
+        self.modules.get(path).copied()
+    }
+
+    /// Finds a declaration by name in the module at `path`. Items the module re-exports are
+    /// followed to the module that declares them, the returned name is the one used there.
+    pub fn find_decl(
+        &self,
+        path: &ModulePath,
+        item: &str,
+    ) -> Option<(String, ItemKind, &ModulePath)> {
+        self.find_decl_rec(path, item, 0)
+    }
+
+    fn find_decl_rec(
+        &self,
+        path: &ModulePath,
+        item: &str,
+        depth: usize,
+    ) -> Option<(String, ItemKind, &ModulePath)> {
+        if depth > MAX_RE_EXPORT_DEPTH {
+            return None;
+        }
+
+        let (path, (_, unit)) = self.modules.get_key_value(path)?;
+        let declared = unit
+            .global_declarations
+            .iter()
+            .filter_map(|decl| decl_ident_and_kind(decl))
+            .find(|(ident, _)| ident.name().as_str() == item);
+        if let Some((_, kind)) = declared {
+            return Some((item.to_string(), kind, path));
+        }
+
+        flatten_imports(path, &unit.imports)
+            .into_iter()
+            .filter(|(idx, _, _, rename)| {
+                rename.0 == item
+                    && unit.imports[*idx]
+                        .attributes
+                        .iter()
+                        .any(|attr| **attr == syntax::Attribute::Publish)
+            })
+            .find_map(|(_, path, item, _)| self.find_decl_rec(&path, &item.0, depth + 1))
+    }
+}
+
+fn load_dependencies(
+    root: &ParsedModule,
+    resolver: &impl Resolver,
+) -> IndexMap<ModulePath, ParsedSource> {
+    fn collect_rec(
+        queue: &mut VecDeque<(ModulePath, String)>,
+        module: &ParsedModule,
+        path: &ModulePath,
+    ) {
+        if let Some(parsed) = &module.parsed {
+            queue.extend(
+                flatten_imports(path, &parsed.syntax.imports)
+                    .into_iter()
+                    .map(|(_, path, item, _)| (path, item.0)),
+            );
+        }
+        for submodule in &module.submodules {
+            let mut path = path.clone();
+            path.components.push(submodule.name.clone());
+            collect_rec(queue, submodule, &path);
+        }
+    }
+
+    let mut queue = VecDeque::new();
+    collect_rec(&mut queue, root, &root_path());
+
+    let mut dependencies = IndexMap::new();
+    let mut visited = HashSet::new();
+    while let Some((path, item)) = queue.pop_front() {
+        // Modules of the package itself are already parsed
+        if !matches!(path.origin, syntax::PathOrigin::Package(_)) {
+            continue;
+        }
+
+        // The import is either an item of the module at `path` or a module itself
+        let mut module_path = path.clone();
+        module_path.components.push(item);
+        for path in [path, module_path] {
+            if !visited.insert(path.clone()) {
+                continue;
+            }
+            let Ok(source) = resolver.resolve_source(&path) else {
+                continue;
+            };
+            // Resolvers may cache parsed modules, so the module is parsed by the resolver
+            let parsed = match resolver.resolve_module(&path) {
+                Ok(syntax) => ParsedSource {
+                    source: source.into_owned(),
+                    syntax,
+                },
+                Err(err) => {
+                    log::warn!(
+                        "failed to parse dependency module {}: {err}",
+                        path.components.join("::")
+                    );
+                    continue;
+                }
+            };
+            queue.extend(
+                flatten_imports(&path, &parsed.syntax.imports)
+                    .into_iter()
+                    .map(|(_, path, item, _)| (path, item.0)),
+            );
+            dependencies.insert(path, parsed);
+        }
+    }
+
+    dependencies
+}
+
+fn root_path() -> ModulePath {
+    ModulePath {
+        origin: syntax::PathOrigin::Absolute,
+        components: Vec::new(),
+    }
+}
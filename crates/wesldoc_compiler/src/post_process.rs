@@ -5,11 +5,12 @@ pub fn post_process(docs: &mut WeslDocs) {
     post_process_module(&mut docs.root);
 }
 
-fn post_process_module(module: &mut Module) -> IndexSet<String> {
+fn post_process_module(module: &mut Module) -> (IndexSet<String>, IndexSet<String>) {
     // Inner modules
     for inner in &mut module.modules {
-        let res_inner = post_process_module(inner);
-        module.translate_time_features.extend(res_inner);
+        let (features_inner, extensions_inner) = post_process_module(inner);
+        module.translate_time_features.extend(features_inner);
+        module.required_extensions.extend(extensions_inner);
     }
 
     // Sort modules
@@ -23,10 +24,23 @@ fn post_process_module(module: &mut Module) -> IndexSet<String> {
     post_process_items(&mut module.functions);
     post_process_items(&mut module.type_aliases);
 
-    // Sort shader defs
+    // Post process re-exports
+    module.re_exports.sort_by(|a, b| a.name.cmp(&b.name));
+    for re_export in &mut module.re_exports {
+        re_export.conditional = re_export
+            .conditional
+            .take()
+            .filter(|conditional| !is_tautology(conditional));
+    }
+
+    // Sort shader defs and extensions
     module.translate_time_features.sort();
+    module.required_extensions.sort();
 
-    module.translate_time_features.clone()
+    (
+        module.translate_time_features.clone(),
+        module.required_extensions.clone(),
+    )
 }
 
 fn post_process_items<T: ItemInstance>(items: &mut IndexMap<Ident, Item<T>>) {
@@ -1,18 +1,22 @@
+use crate::GenerateOptions;
 use serde::Serialize;
 use wesldoc_ast::{
-    Attribute, DocComment, Ident, IndexMap, Item, ItemInstance, ItemKind, Module, WeslDocs,
+    Attribute, DefinitionPath, DocComment, Ident, IndexMap, Item, ItemInstance, ItemKind, Module,
+    ReExport, WeslDocs,
 };
 
-pub fn all_items(doc: &WeslDocs) -> impl Serialize {
+pub fn all_items(doc: &WeslDocs, options: &GenerateOptions) -> impl Serialize {
     let mut items = Vec::new();
-    all_items_module(&doc.root, &[], &mut items);
+    all_items_module(&doc.root, &doc.root, &[], options, &mut items);
     items.sort();
     items
 }
 
 fn all_items_module(
+    root: &Module,
     module: &Module,
     parent: &[String],
+    options: &GenerateOptions,
     serialized_items: &mut Vec<SerializedItem>,
 ) {
     let path = parent
@@ -21,7 +25,7 @@ fn all_items_module(
         .chain([module.name.clone()])
         .collect::<Vec<_>>();
     for inner in &module.modules {
-        all_items_module(inner, &path, serialized_items);
+        all_items_module(root, inner, &path, options, serialized_items);
 
         serialized_items.push(SerializedItem::new(
             path.clone(),
@@ -38,6 +42,81 @@ fn all_items_module(
     add_items(&module.structs, path.clone(), serialized_items);
     add_items(&module.functions, path.clone(), serialized_items);
     add_items(&module.type_aliases, path.clone(), serialized_items);
+
+    // Re-exported items are found under the re-exporting module, but link to the canonical item
+    for re_export in &module.re_exports {
+        let comment = match &re_export.def_path {
+            DefinitionPath::Absolute(components) => {
+                item_comment(root, components, re_export.kind, &re_export.original_name)
+            }
+            DefinitionPath::Package(..) => None,
+        };
+        let mut item = SerializedItem::new(
+            path.clone(),
+            re_export.name.0.clone(),
+            [],
+            comment,
+            re_export.kind.into(),
+        );
+        item.url = re_export_url(re_export, &root.name, options);
+        serialized_items.push(item);
+    }
+}
+
+/// The doc comment of an item of the documented package.
+fn item_comment<'a>(
+    root: &'a Module,
+    components: &[String],
+    kind: ItemKind,
+    name: &Ident,
+) -> Option<&'a DocComment> {
+    let module = components.iter().try_fold(root, |module, component| {
+        module.modules.iter().find(|inner| inner.name == *component)
+    })?;
+    match kind {
+        ItemKind::Module => module.comment.as_ref(),
+        ItemKind::Constant => module.constants.get(name)?.instances[0].comment(),
+        ItemKind::Override => module.overrides.get(name)?.instances[0].comment(),
+        ItemKind::GlobalVariable => module.global_variables.get(name)?.instances[0].comment(),
+        ItemKind::Struct => module.structs.get(name)?.instances[0].comment(),
+        ItemKind::Function => module.functions.get(name)?.instances[0].comment(),
+        ItemKind::TypeAlias => module.type_aliases.get(name)?.instances[0].comment(),
+    }
+}
+
+/// URL of the canonical item, relative to the directory of `items.js`. Items of dependencies that
+/// are hosted elsewhere get an absolute URL.
+fn re_export_url(re_export: &ReExport, package: &str, options: &GenerateOptions) -> String {
+    let mut url = match &re_export.def_path {
+        DefinitionPath::Absolute(components) => [package]
+            .into_iter()
+            .chain(components.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("/"),
+        DefinitionPath::Package(dep, version, components) => {
+            let root_url = match options.extern_html_root_urls.get(dep) {
+                Some(root_url) => root_url.trim_end_matches('/').to_string(),
+                None => format!("../../{dep}/{version}"),
+            };
+            [root_url.as_str(), dep]
+                .into_iter()
+                .chain(components.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join("/")
+        }
+    };
+
+    let name = &re_export.original_name.0;
+    match re_export.kind {
+        ItemKind::Module => url.push_str("/index.html"),
+        ItemKind::Constant => url.push_str(&format!("/const.{name}.html")),
+        ItemKind::Override => url.push_str(&format!("/override.{name}.html")),
+        ItemKind::GlobalVariable => url.push_str(&format!("/var.{name}.html")),
+        ItemKind::Struct => url.push_str(&format!("/struct.{name}.html")),
+        ItemKind::Function => url.push_str(&format!("/fn.{name}.html")),
+        ItemKind::TypeAlias => url.push_str(&format!("/alias.{name}.html")),
+    }
+    url
 }
 
 fn add_items<T>(
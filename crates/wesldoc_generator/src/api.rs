@@ -0,0 +1,454 @@
+use crate::render::{
+    builtin_str, conservative_depth_str, interpolation_str, sampling_str, severity_str,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+use wesldoc_ast::{
+    Attribute, Conditional, Constant, DefinitionPath, Expression, Function, GlobalVariable, Item,
+    ItemKind, Module, Override, ReExport, Struct, TypeAlias, TypeExpression, Version, WeslDocs,
+};
+
+/// The public API of a package, reduced to what consumers of the package depend on. It is
+/// stored as `api.json` next to every documented version, so versions can be compared without
+/// their sources.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Api {
+    pub package: String,
+    #[serde(with = "version_serde")]
+    pub version: Version,
+    /// Items keyed by their kind and path, e.g. `fn lighting::shade`.
+    pub items: BTreeMap<String, ApiItem>,
+    /// Translate-time features used anywhere in the package.
+    #[serde(default)]
+    pub features: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiItem {
+    /// The WGSL keyword of the item kind, e.g. `fn` or `struct`.
+    pub kind: String,
+    /// The path relative to the package root, e.g. `lighting::shade`.
+    pub path: String,
+    /// One instance per conditional declaration of the item.
+    pub instances: Vec<ApiInstance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiInstance {
+    pub conditional: Option<String>,
+    pub signature: String,
+    /// The signature of a function without its parameter names. Calls are positional, so this
+    /// is what callers depend on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<ApiMember>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiMember {
+    pub name: String,
+    pub ty: String,
+    /// Layout and IO attributes such as `@align(16)` or `@location(0)`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conditional: Option<String>,
+}
+
+impl Api {
+    pub fn of(docs: &WeslDocs) -> Self {
+        let mut items = BTreeMap::new();
+        add_module(&docs.root, &[], &mut items);
+        Self {
+            package: docs.root.name.clone(),
+            version: docs.version.clone(),
+            items,
+            features: docs.root.translate_time_features.iter().cloned().collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn store(&self, path: &Path) -> crate::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn add_module(module: &Module, parent: &[String], items: &mut BTreeMap<String, ApiItem>) {
+    let child_path = |name: &str| {
+        parent
+            .iter()
+            .map(String::as_str)
+            .chain([name])
+            .collect::<Vec<_>>()
+            .join("::")
+    };
+    let mut add = |kind: ItemKind, name: &str, instances: Vec<ApiInstance>| {
+        let kind = kind_str(kind).to_string();
+        let path = child_path(name);
+        items.insert(
+            format!("{kind} {path}"),
+            ApiItem {
+                kind,
+                path,
+                instances,
+            },
+        );
+    };
+
+    for inner in &module.modules {
+        add(
+            ItemKind::Module,
+            &inner.name,
+            vec![ApiInstance::new(None, format!("mod {}", inner.name))],
+        );
+    }
+    for re_export in &module.re_exports {
+        add(
+            re_export.kind,
+            &re_export.name.0,
+            vec![ApiInstance::new(
+                re_export.conditional.as_ref(),
+                format!("@publish import {}", re_export_path(re_export)),
+            )],
+        );
+    }
+    for (name, item) in &module.constants {
+        add(ItemKind::Constant, &name.0, instances(item, constant));
+    }
+    for (name, item) in &module.overrides {
+        add(ItemKind::Override, &name.0, instances(item, override_));
+    }
+    for (name, item) in &module.global_variables {
+        add(
+            ItemKind::GlobalVariable,
+            &name.0,
+            instances(item, global_variable),
+        );
+    }
+    for (name, item) in &module.structs {
+        add(ItemKind::Struct, &name.0, instances(item, struct_));
+    }
+    for (name, item) in &module.functions {
+        add(ItemKind::Function, &name.0, instances(item, function));
+    }
+    for (name, item) in &module.type_aliases {
+        add(ItemKind::TypeAlias, &name.0, instances(item, type_alias));
+    }
+
+    for inner in &module.modules {
+        let path = parent
+            .iter()
+            .cloned()
+            .chain([inner.name.clone()])
+            .collect::<Vec<_>>();
+        add_module(inner, &path, items);
+    }
+}
+
+fn instances<T>(item: &Item<T>, f: impl Fn(&T) -> ApiInstance) -> Vec<ApiInstance> {
+    item.instances.iter().map(f).collect()
+}
+
+impl ApiInstance {
+    fn new(conditional: Option<&Conditional>, signature: String) -> Self {
+        Self {
+            conditional: conditional.map(ToString::to_string),
+            signature,
+            call_signature: None,
+            members: Vec::new(),
+            value: None,
+            binding: None,
+            attributes: Vec::new(),
+        }
+    }
+
+    fn with_attributes(mut self, attributes: &[Attribute]) -> Self {
+        let (binding, other): (Vec<_>, Vec<_>) = attributes
+            .iter()
+            .partition(|attr| matches!(attr, Attribute::Group(_) | Attribute::Binding(_)));
+        if !binding.is_empty() {
+            self.binding = Some(attributes_str(binding));
+        }
+        self.attributes = other.into_iter().map(attribute_str).collect();
+        self
+    }
+}
+
+fn constant(constant: &Constant) -> ApiInstance {
+    let mut instance = ApiInstance::new(
+        constant.conditional.as_ref(),
+        format!("const {}{}", constant.name, ty_suffix(constant.ty.as_ref())),
+    )
+    .with_attributes(&constant.attributes);
+    instance.value = Some(expression_str(&constant.init));
+    instance
+}
+
+fn override_(override_: &Override) -> ApiInstance {
+    let mut instance = ApiInstance::new(
+        override_.conditional.as_ref(),
+        format!(
+            "override {}{}",
+            override_.name,
+            ty_suffix(override_.ty.as_ref())
+        ),
+    )
+    .with_attributes(&override_.attributes);
+    instance.value = override_.init.as_ref().map(expression_str);
+    instance
+}
+
+fn global_variable(variable: &GlobalVariable) -> ApiInstance {
+    let mut instance = ApiInstance::new(
+        variable.conditional.as_ref(),
+        format!(
+            "var{} {}{}",
+            variable.space,
+            variable.name,
+            ty_suffix(variable.ty.as_ref())
+        ),
+    )
+    .with_attributes(&variable.attributes);
+    instance.value = variable.init.as_ref().map(expression_str);
+    instance
+}
+
+fn struct_(struct_: &Struct) -> ApiInstance {
+    let mut instance = ApiInstance::new(
+        struct_.conditional.as_ref(),
+        format!("struct {}", struct_.name),
+    )
+    .with_attributes(&struct_.attributes);
+    instance.members = struct_
+        .members
+        .iter()
+        .map(|member| ApiMember {
+            name: member.name.0.clone(),
+            ty: type_str(&member.ty),
+            attributes: member.attributes.iter().map(attribute_str).collect(),
+            conditional: member.conditional.as_ref().map(ToString::to_string),
+        })
+        .collect();
+    instance
+}
+
+fn function(function: &Function) -> ApiInstance {
+    let parameters = |with_names: bool| {
+        function
+            .parameters
+            .iter()
+            .map(|param| {
+                let conditional = param
+                    .conditional
+                    .as_ref()
+                    .map(|conditional| format!("@if({conditional}) "))
+                    .unwrap_or_default();
+                let name = if with_names {
+                    format!("{}: ", param.name)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{conditional}{}{name}{}",
+                    attributes_prefix(&param.attributes),
+                    type_str(&param.ty)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let ret = function
+        .ret
+        .as_ref()
+        .map(|ret| {
+            format!(
+                " -> {}{}",
+                attributes_prefix(&function.return_attributes),
+                type_str(ret)
+            )
+        })
+        .unwrap_or_default();
+    let mut instance = ApiInstance::new(
+        function.conditional.as_ref(),
+        format!("fn {}({}){ret}", function.name, parameters(true)),
+    )
+    .with_attributes(&function.attributes);
+    instance.call_signature = Some(format!("fn {}({}){ret}", function.name, parameters(false)));
+    instance
+}
+
+fn type_alias(alias: &TypeAlias) -> ApiInstance {
+    ApiInstance::new(
+        alias.conditional.as_ref(),
+        format!("alias {} = {}", alias.name, type_str(&alias.ty)),
+    )
+    .with_attributes(&alias.attributes)
+}
+
+pub(crate) fn kind_str(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Module => "mod",
+        ItemKind::Constant => "const",
+        ItemKind::Override => "override",
+        ItemKind::GlobalVariable => "var",
+        ItemKind::Struct => "struct",
+        ItemKind::Function => "fn",
+        ItemKind::TypeAlias => "alias",
+    }
+}
+
+fn re_export_path(re_export: &ReExport) -> String {
+    let mut path = match &re_export.def_path {
+        DefinitionPath::Absolute(components) => ["package".to_string()]
+            .into_iter()
+            .chain(components.iter().cloned())
+            .collect::<Vec<_>>(),
+        DefinitionPath::Package(package, _, components) => [package.clone()]
+            .into_iter()
+            .chain(components.iter().cloned())
+            .collect::<Vec<_>>(),
+    };
+    if re_export.kind != ItemKind::Module {
+        path.push(re_export.original_name.0.clone());
+    }
+    path.join("::")
+}
+
+fn ty_suffix(ty: Option<&TypeExpression>) -> String {
+    ty.map(|ty| format!(": {}", type_str(ty)))
+        .unwrap_or_default()
+}
+
+/// Renders a type as WGSL source. Referenced types are rendered by name, so moving a type to
+/// another module of the same package is not reported as a signature change.
+fn type_str(ty: &TypeExpression) -> String {
+    match ty {
+        TypeExpression::TypeIdentifier {
+            name,
+            template_args,
+        } => match template_args {
+            Some(args) => format!(
+                "{name}<{}>",
+                args.iter()
+                    .map(expression_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => name.to_string(),
+        },
+        TypeExpression::Referenced { name, .. } => name.to_string(),
+        TypeExpression::Atomic(inner) => format!("atomic<{}>", type_str(inner)),
+    }
+}
+
+fn expression_str(expr: &Expression) -> String {
+    match expr {
+        Expression::Literal(literal) => literal.to_string(),
+        Expression::Parenthesized(inner) => format!("({})", expression_str(inner)),
+        Expression::TypeOrIdentifier(ty) => type_str(ty),
+        Expression::NotExpanded(_) => "...".to_string(),
+    }
+}
+
+fn attributes_prefix(attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .map(|attr| format!("{} ", attribute_str(attr)))
+        .collect()
+}
+
+fn attributes_str(attributes: Vec<&Attribute>) -> String {
+    attributes
+        .into_iter()
+        .map(attribute_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn attribute_str(attr: &Attribute) -> String {
+    let name = attr.name();
+    match attr {
+        Attribute::Align(expr)
+        | Attribute::Binding(expr)
+        | Attribute::BlendSrc(expr)
+        | Attribute::Group(expr)
+        | Attribute::Id(expr)
+        | Attribute::Location(expr)
+        | Attribute::Size(expr)
+        | Attribute::Payload(expr)
+        | Attribute::Mesh(expr) => format!("@{name}({})", expression_str(expr)),
+        Attribute::Builtin(builtin) => format!("@{name}({})", builtin_str(builtin)),
+        Attribute::Diagnostic { severity, rule } => {
+            format!("@{name}({}, {rule})", severity_str(severity))
+        }
+        Attribute::Interpolate { ty, sampling } => match sampling {
+            Some(sampling) => format!(
+                "@{name}({}, {})",
+                interpolation_str(ty),
+                sampling_str(sampling)
+            ),
+            None => format!("@{name}({})", interpolation_str(ty)),
+        },
+        Attribute::WorkgroupSize { x, y, z } => {
+            let args = [Some(x), y.as_deref(), z.as_deref()]
+                .into_iter()
+                .flatten()
+                .map(expression_str)
+                .collect::<Vec<_>>();
+            format!("@{name}({})", args.join(", "))
+        }
+        Attribute::EarlyDepthTest(Some(depth)) => {
+            format!("@{name}({})", conservative_depth_str(depth))
+        }
+        Attribute::Custom {
+            arguments: Some(arguments),
+            ..
+        } => format!(
+            "@{name}({})",
+            arguments
+                .iter()
+                .map(expression_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Attribute::Const
+        | Attribute::Invariant
+        | Attribute::MustUse
+        | Attribute::Vertex
+        | Attribute::Fragment
+        | Attribute::Compute
+        | Attribute::Task
+        | Attribute::EarlyDepthTest(None)
+        | Attribute::Custom {
+            arguments: None, ..
+        } => format!("@{name}"),
+    }
+}
+
+mod version_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(version: &Version, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(version)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Version, D::Error> {
+        let version = String::deserialize(deserializer)?;
+        Version::parse(&version).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{GenerateOptions, Result, context::Context};
+use askama::Template;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+use wesldoc_ast::{Version, WeslDocs};
+
+const CACHE_FILE: &str = ".wesldoc-cache.json";
+
+/// Fingerprints of what a documented version was generated from, stored next to its pages. The
+/// fingerprints are not stable across wesldoc versions, a mismatch only causes a rebuild.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildCache {
+    /// The fingerprint of the sources and options the package was compiled from, set by the
+    /// caller through [`GenerateOptions::source_fingerprint`].
+    #[serde(default)]
+    source: Option<String>,
+    /// The fingerprint of everything all pages depend on.
+    package: String,
+    /// The fingerprints of the pages of each module, keyed by module path.
+    modules: BTreeMap<String, String>,
+}
+
+impl BuildCache {
+    fn load(tree: &Path) -> Option<Self> {
+        serde_json::from_slice(&fs::read(tree.join(CACHE_FILE)).ok()?).ok()
+    }
+}
+
+pub fn is_up_to_date(
+    base_path: &Path,
+    package: &str,
+    version: &Version,
+    fingerprint: &str,
+) -> bool {
+    BuildCache::load(&base_path.join(package).join(version.to_string()))
+        .is_some_and(|cache| cache.source.as_deref() == Some(fingerprint))
+}
+
+/// A documented version that is written into a temporary directory first and moved into place
+/// once complete, so a failed run never leaves half-written docs behind. Pages of modules that
+/// did not change are taken over from the previous docs instead of being rendered again.
+pub(crate) struct OutputTree {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    previous: BuildCache,
+    current: BuildCache,
+}
+
+impl OutputTree {
+    pub fn create(path: PathBuf, doc: &WeslDocs, options: &GenerateOptions) -> Result<Self> {
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let tmp_path = path.with_file_name(format!(".{file_name}.tmp"));
+        fs::remove_dir_all(&tmp_path).ok();
+        fs::create_dir_all(&tmp_path)?;
+
+        let package = package_fingerprint(&file_name, doc, options);
+        let previous = BuildCache::load(&path)
+            .filter(|previous| previous.package == package)
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            tmp_path,
+            previous,
+            current: BuildCache {
+                source: options.source_fingerprint.clone(),
+                package,
+                modules: BTreeMap::new(),
+            },
+        })
+    }
+
+    /// The directory the docs are written to until [`OutputTree::finish`] is called.
+    pub fn tmp_path(&self) -> &Path {
+        &self.tmp_path
+    }
+
+    /// Records the fingerprint of a module and returns whether its pages can be reused.
+    pub fn is_unchanged(&mut self, ctx: &Context) -> bool {
+        let key = ctx
+            .segments()
+            .map(|(name, _, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        let fingerprint = format!("{:016x}", ctx.module_fingerprint());
+        let unchanged = self.previous.modules.get(&key) == Some(&fingerprint);
+        self.current.modules.insert(key, fingerprint);
+        unchanged
+    }
+
+    /// Writes a page, or links the previous page if `reuse` is set and it still exists.
+    pub fn write_page(&self, path: &Path, reuse: bool, template: &impl Template) -> Result<()> {
+        if reuse && let Ok(relative) = path.strip_prefix(&self.tmp_path) {
+            let previous = self.path.join(relative);
+            if previous.is_file() {
+                fs::hard_link(&previous, path).or_else(|_| fs::copy(&previous, path).map(drop))?;
+                return Ok(());
+            }
+        }
+        Template::write_into(template, &mut File::create(path)?)?;
+        Ok(())
+    }
+
+    /// Stores the fingerprints and replaces the previous docs.
+    pub fn finish(self) -> Result<()> {
+        fs::write(
+            self.tmp_path.join(CACHE_FILE),
+            serde_json::to_string_pretty(&self.current)?,
+        )?;
+
+        let old_path = self.tmp_path.with_extension("old");
+        fs::remove_dir_all(&old_path).ok();
+        if self.path.exists() {
+            fs::rename(&self.path, &old_path)?;
+        }
+        fs::rename(&self.tmp_path, &self.path)?;
+        fs::remove_dir_all(&old_path).ok();
+
+        Ok(())
+    }
+}
+
+/// Hashes what all pages of a documented version depend on besides their module.
+fn package_fingerprint(tree: &str, doc: &WeslDocs, options: &GenerateOptions) -> String {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    tree.hash(&mut hasher);
+    doc.root.name.hash(&mut hasher);
+    doc.version.hash(&mut hasher);
+    options
+        .extern_html_root_urls
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
@@ -1,24 +1,30 @@
-use std::str::FromStr;
+use crate::{GenerateOptions, api::kind_str, history::ItemHistory};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    str::FromStr,
+};
 
 use wesldoc_ast::{
-    Attribute, DefinitionPath, DocComment, Expression, Ident, IntraDocLink, ItemKind, Module, Span,
-    TypeExpression, WeslDocs, md,
+    Attribute, Coverage, CoverageCount, DefinitionPath, DocComment, Expression, Ident,
+    IntraDocLink, ItemKind, Module, Span, TypeExpression, WeslDocs, md,
 };
 
 #[derive(Debug)]
 pub struct Context<'a> {
-    pub build_as_latest: bool,
     pub doc: &'a WeslDocs,
     pub module: &'a Module,
+    options: &'a GenerateOptions,
+    history: &'a ItemHistory,
     module_path: ModulePath,
 }
 
 impl<'a> Context<'a> {
-    pub fn new(build_as_latest: bool, doc: &'a WeslDocs) -> Self {
+    pub fn new(doc: &'a WeslDocs, options: &'a GenerateOptions, history: &'a ItemHistory) -> Self {
         Self {
-            build_as_latest,
             doc,
             module: &doc.root,
+            options,
+            history,
             module_path: ModulePath {
                 segments: vec![(
                     doc.root.name.clone(),
@@ -32,9 +38,10 @@ impl<'a> Context<'a> {
 
     pub fn with_submodule(&self, module: &'a Module) -> Self {
         Self {
-            build_as_latest: self.build_as_latest,
             doc: self.doc,
             module,
+            options: self.options,
+            history: self.history,
             module_path: self.module_path.extend(
                 &module.name,
                 "index.html",
@@ -46,9 +53,10 @@ impl<'a> Context<'a> {
 
     pub fn with_item(&self, name: impl Into<String>, kind: ItemKind) -> Self {
         Self {
-            build_as_latest: self.build_as_latest,
             doc: self.doc,
             module: self.module,
+            options: self.options,
+            history: self.history,
             module_path: self.module_path.extend(name, "#", kind, false),
         }
     }
@@ -57,6 +65,62 @@ impl<'a> Context<'a> {
         self.module_path.level
     }
 
+    /// Documentation coverage of the whole package.
+    pub fn coverage(&self) -> CoverageCount {
+        Coverage::of(self.doc).total()
+    }
+
+    /// Hashes everything the pages of the current module are rendered from: the module, the
+    /// summaries of its submodules and the versions of its items. Links only depend on the paths
+    /// of the linked items, which are part of the module.
+    pub fn module_fingerprint(&self) -> u64 {
+        let module = self.module;
+        let mut hasher = DefaultHasher::new();
+        format!(
+            "{:?}",
+            (
+                &module.name,
+                &module.source,
+                &module.error,
+                &module.comment,
+                &module.constants,
+                &module.overrides,
+                &module.global_variables,
+                &module.structs,
+            )
+        )
+        .hash(&mut hasher);
+        format!(
+            "{:?}",
+            (
+                &module.functions,
+                &module.type_aliases,
+                &module.re_exports,
+                &module.directives,
+                &module.const_asserts,
+                &module.translate_time_features,
+                &module.required_extensions,
+            )
+        )
+        .hash(&mut hasher);
+        for submodule in &module.modules {
+            format!("{:?}", (&submodule.name, &submodule.comment)).hash(&mut hasher);
+        }
+
+        let path = self.module_path.segments[1..]
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect::<Vec<_>>();
+        format!("{:?}", self.history.module_entries(&path)).hash(&mut hasher);
+
+        // The overview of the root module shows the coverage of the whole package
+        if self.level() == 0 {
+            format!("{:?}", self.coverage()).hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
     pub fn segments(&self) -> impl Iterator<Item = &(String, String, ItemKind)> {
         self.module_path.segments.iter()
     }
@@ -70,7 +134,74 @@ impl<'a> Context<'a> {
     }
 
     pub fn def_path_url(&self, name: &Ident, kind: &ItemKind, def_path: &DefinitionPath) -> String {
-        self.module_path.def_path_url(name, kind, def_path)
+        let extern_root_url = match def_path {
+            DefinitionPath::Package(dep, _, _) => self.options.extern_html_root_urls.get(dep),
+            DefinitionPath::Absolute(_) => None,
+        };
+        self.module_path
+            .def_path_url(name, kind, def_path, extern_root_url.map(String::as_str))
+    }
+
+    /// Badge for an item of the current module in an overview list.
+    pub fn render_since_inline(
+        &self,
+        kind: ItemKind,
+        name: &str,
+        comment: Option<&DocComment>,
+    ) -> String {
+        let path = self.module_path.segments[1..]
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .chain([name])
+            .collect::<Vec<_>>();
+        match self.since(kind, &path, comment) {
+            Some(since) => format!(r#" <span class="since">since {since}</span>"#),
+            None => String::new(),
+        }
+    }
+
+    /// Badges for the item or module of the current page.
+    pub fn render_since(&self, comment: Option<&DocComment>) -> String {
+        let Some((_, _, kind)) = self.module_path.segments.last() else {
+            return String::new();
+        };
+        let path = self.module_path.segments[1..]
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect::<Vec<_>>();
+        if path.is_empty() {
+            return String::new();
+        }
+
+        let mut badges = Vec::new();
+        if let Some(since) = self.since(*kind, &path, comment) {
+            badges.push(format!(r#"<span class="since">since {since}</span>"#));
+        }
+        if let Some(changed) = self
+            .history
+            .get(&format!("{} {}", kind_str(*kind), path.join("::")))
+            .and_then(|versions| versions.changed.as_ref())
+        {
+            badges.push(format!(
+                r#"<span class="since">changed in {changed}</span>"#
+            ));
+        }
+        match badges.is_empty() {
+            true => String::new(),
+            false => format!("<div>{}</div>", badges.join(" ")),
+        }
+    }
+
+    /// The version an item was introduced in. An explicit `@since` tag takes precedence over the
+    /// version computed from the documented versions.
+    fn since(&self, kind: ItemKind, path: &[&str], comment: Option<&DocComment>) -> Option<String> {
+        if let Some(since) = comment.and_then(|comment| comment.since.as_ref()) {
+            return Some(since.to_string());
+        }
+        self.history
+            .get(&format!("{} {}", kind_str(kind), path.join("::")))
+            .and_then(|versions| versions.since.as_ref())
+            .map(ToString::to_string)
     }
 
     pub fn render_attributes(
@@ -262,7 +393,15 @@ impl ModulePath {
         url
     }
 
-    fn def_path_url(&self, name: &Ident, kind: &ItemKind, def_path: &DefinitionPath) -> String {
+    /// Links into a dependency are relative to its docs in the same output directory, or
+    /// absolute if the dependency is hosted at `extern_root_url`.
+    fn def_path_url(
+        &self,
+        name: &Ident,
+        kind: &ItemKind,
+        def_path: &DefinitionPath,
+        extern_root_url: Option<&str>,
+    ) -> String {
         let mut url = String::new();
 
         match def_path {
@@ -276,15 +415,23 @@ impl ModulePath {
                 }
             }
             DefinitionPath::Package(dep, version, components) => {
-                for _ in 0..self.level + 3 {
-                    url.push_str("../");
+                match extern_root_url {
+                    Some(root_url) => {
+                        url.push_str(root_url.trim_end_matches('/'));
+                        url.push('/');
+                    }
+                    None => {
+                        for _ in 0..self.level + 3 {
+                            url.push_str("../");
+                        }
+                        url.push_str(dep);
+                        url.push('/');
+                        url.push_str(&version.to_string());
+                        url.push('/');
+                    }
                 }
                 url.push_str(dep);
                 url.push('/');
-                url.push_str(&version.to_string());
-                url.push('/');
-                url.push_str(dep);
-                url.push('/');
                 for c in components {
                     url.push_str(c);
                     url.push('/');
@@ -0,0 +1,426 @@
+use crate::api::{Api, ApiInstance, ApiItem, ApiMember};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wesldoc_ast::Version;
+
+/// Changes of the public API between two versions of a package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiDiff {
+    pub package: String,
+    pub old_version: String,
+    pub new_version: String,
+    pub changes: Vec<ApiChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiChange {
+    pub kind: ChangeKind,
+    /// The WGSL keyword of the item kind, e.g. `fn` or `struct`.
+    pub item_kind: String,
+    pub path: String,
+    pub message: String,
+    /// Whether the change can break consumers of the package.
+    pub breaking: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// The signature of a function, the type of a constant, override or variable, or the aliased
+    /// type changed.
+    Signature,
+    /// Struct members were added, removed or changed their type.
+    Members,
+    /// Struct members were reordered or their layout attributes changed.
+    Layout,
+    /// The value of a constant, override or variable changed.
+    Value,
+    /// `@group` or `@binding` of a variable changed.
+    Binding,
+    Attributes,
+    /// The conditions under which the item is declared changed.
+    Conditional,
+}
+
+impl ChangeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Signature => "signature",
+            ChangeKind::Members => "members",
+            ChangeKind::Layout => "layout",
+            ChangeKind::Value => "value",
+            ChangeKind::Binding => "binding",
+            ChangeKind::Attributes => "attributes",
+            ChangeKind::Conditional => "conditional",
+        }
+    }
+
+    /// Whether changes of this kind break consumers by default. Struct members are breaking
+    /// because their layout is shared with host code, e.g. in uniform buffers.
+    fn is_breaking(&self) -> bool {
+        match self {
+            ChangeKind::Removed
+            | ChangeKind::Signature
+            | ChangeKind::Members
+            | ChangeKind::Layout
+            | ChangeKind::Binding
+            | ChangeKind::Conditional => true,
+            ChangeKind::Added | ChangeKind::Value | ChangeKind::Attributes => false,
+        }
+    }
+}
+
+impl ApiDiff {
+    pub fn between(old: &Api, new: &Api) -> Self {
+        let mut changes = Vec::new();
+        for (key, old_item) in &old.items {
+            match new.items.get(key) {
+                Some(new_item) => diff_item(old_item, new_item, &mut changes),
+                None => changes.push(ApiChange::new(
+                    ChangeKind::Removed,
+                    old_item,
+                    "removed".to_string(),
+                )),
+            }
+        }
+        for (key, new_item) in &new.items {
+            if !old.items.contains_key(key) {
+                changes.push(ApiChange::new(
+                    ChangeKind::Added,
+                    new_item,
+                    "added".to_string(),
+                ));
+            }
+        }
+        for feature in old.features.difference(&new.features) {
+            changes.push(ApiChange::feature(ChangeKind::Removed, feature));
+        }
+        for feature in new.features.difference(&old.features) {
+            changes.push(ApiChange::feature(ChangeKind::Added, feature));
+        }
+
+        Self {
+            package: new.package.clone(),
+            old_version: old.version.to_string(),
+            new_version: new.version.to_string(),
+            changes,
+        }
+    }
+
+    pub fn old_version(&self) -> Option<Version> {
+        Version::parse(&self.old_version).ok()
+    }
+
+    pub fn new_version(&self) -> Option<Version> {
+        Version::parse(&self.new_version).ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &ApiChange> {
+        self.changes.iter().filter(|change| change.breaking)
+    }
+
+    /// Whether the version bump allows breaking changes. Like cargo, this is a major bump, a
+    /// minor bump for `0.x` versions or any bump for `0.0.x` versions.
+    pub fn allows_breaking_changes(&self) -> bool {
+        let (Some(old), Some(new)) = (self.old_version(), self.new_version()) else {
+            return false;
+        };
+        if old.major != 0 || new.major != old.major {
+            new.major > old.major
+        } else if old.minor != 0 || new.minor != old.minor {
+            new.minor > old.minor
+        } else {
+            new.patch > old.patch
+        }
+    }
+
+    /// Describes why the version bump is too small for the breaking changes, if it is.
+    pub fn semver_violation(&self) -> Option<String> {
+        let breaking = self.breaking_changes().count();
+        if breaking == 0 || self.allows_breaking_changes() {
+            return None;
+        }
+        let required = match self.old_version() {
+            Some(old) if old.major == 0 && old.minor == 0 => "patch",
+            Some(old) if old.major == 0 => "minor",
+            _ => "major",
+        };
+        Some(format!(
+            "{} {} -> {} has {breaking} breaking change(s) but no {required} version bump",
+            self.package, self.old_version, self.new_version,
+        ))
+    }
+}
+
+impl ApiChange {
+    fn new(kind: ChangeKind, item: &ApiItem, message: String) -> Self {
+        Self {
+            kind,
+            item_kind: item.kind.clone(),
+            path: item.path.clone(),
+            message,
+            breaking: kind.is_breaking(),
+            old: None,
+            new: None,
+        }
+    }
+
+    /// A translate-time feature that is no longer used is breaking, because code that enables
+    /// it silently loses the behaviour it selected.
+    fn feature(kind: ChangeKind, name: &str) -> Self {
+        Self {
+            kind,
+            item_kind: "feature".to_string(),
+            path: name.to_string(),
+            message: kind.name().to_string(),
+            breaking: kind.is_breaking(),
+            old: None,
+            new: None,
+        }
+    }
+
+    fn with_breaking(mut self, breaking: bool) -> Self {
+        self.breaking = breaking;
+        self
+    }
+
+    fn with_values(mut self, old: impl Into<String>, new: impl Into<String>) -> Self {
+        self.old = Some(old.into());
+        self.new = Some(new.into());
+        self
+    }
+
+    /// The changed item, e.g. `fn lighting::shade`.
+    pub fn item(&self) -> String {
+        format!("{} {}", self.item_kind, self.path)
+    }
+}
+
+fn diff_item(old: &ApiItem, new: &ApiItem, changes: &mut Vec<ApiChange>) {
+    let old_conditionals = conditionals(&old.instances);
+    let new_conditionals = conditionals(&new.instances);
+    if old_conditionals != new_conditionals {
+        // Only declaring the item unconditionally or under additional conditions is compatible
+        let narrowed = !new_conditionals.iter().any(|c| c == "always")
+            && old_conditionals
+                .iter()
+                .any(|conditional| !new_conditionals.contains(conditional));
+        changes.push(
+            ApiChange::new(
+                ChangeKind::Conditional,
+                new,
+                "declared under different conditions".to_string(),
+            )
+            .with_values(old_conditionals.join(" | "), new_conditionals.join(" | "))
+            .with_breaking(narrowed),
+        );
+    }
+
+    // Compare instances with the same condition, or the only instances if the condition changed
+    let pairs = match (&old.instances[..], &new.instances[..]) {
+        ([old_instance], [new_instance]) => vec![(old_instance, new_instance)],
+        _ => old
+            .instances
+            .iter()
+            .filter_map(|old_instance| {
+                new.instances
+                    .iter()
+                    .find(|new_instance| new_instance.conditional == old_instance.conditional)
+                    .map(|new_instance| (old_instance, new_instance))
+            })
+            .collect(),
+    };
+    for (old_instance, new_instance) in pairs {
+        diff_instance(new, old_instance, new_instance, changes);
+    }
+}
+
+fn conditionals(instances: &[ApiInstance]) -> Vec<String> {
+    instances
+        .iter()
+        .map(|instance| {
+            instance
+                .conditional
+                .clone()
+                .unwrap_or_else(|| "always".to_string())
+        })
+        .collect()
+}
+
+/// Compares the call signatures where both are known, so renaming a parameter is not a change. APIs
+/// stored by older versions only have the full signature.
+fn signature_changed(old: &ApiInstance, new: &ApiInstance) -> bool {
+    match (&old.call_signature, &new.call_signature) {
+        (Some(old), Some(new)) => old != new,
+        _ => old.signature != new.signature,
+    }
+}
+
+fn diff_instance(
+    item: &ApiItem,
+    old: &ApiInstance,
+    new: &ApiInstance,
+    changes: &mut Vec<ApiChange>,
+) {
+    if signature_changed(old, new) {
+        changes.push(
+            ApiChange::new(ChangeKind::Signature, item, "signature changed".to_string())
+                .with_values(&old.signature, &new.signature),
+        );
+    }
+    diff_members(item, &old.members, &new.members, changes);
+    if old.value != new.value {
+        changes.push(
+            ApiChange::new(ChangeKind::Value, item, "value changed".to_string()).with_values(
+                old.value.as_deref().unwrap_or("none"),
+                new.value.as_deref().unwrap_or("none"),
+            ),
+        );
+    }
+    if old.binding != new.binding {
+        changes.push(
+            ApiChange::new(ChangeKind::Binding, item, "binding changed".to_string()).with_values(
+                old.binding.as_deref().unwrap_or("none"),
+                new.binding.as_deref().unwrap_or("none"),
+            ),
+        );
+    }
+    if old.attributes != new.attributes {
+        changes.push(
+            ApiChange::new(
+                ChangeKind::Attributes,
+                item,
+                "attributes changed".to_string(),
+            )
+            .with_values(old.attributes.join(" "), new.attributes.join(" ")),
+        );
+    }
+}
+
+fn diff_members(
+    item: &ApiItem,
+    old: &[ApiMember],
+    new: &[ApiMember],
+    changes: &mut Vec<ApiChange>,
+) {
+    let find = |members: &'_ [ApiMember], name: &str| {
+        members.iter().position(|member| member.name == name)
+    };
+
+    for old_member in old {
+        let Some(new_member) = find(new, &old_member.name).map(|idx| &new[idx]) else {
+            changes.push(ApiChange::new(
+                ChangeKind::Members,
+                item,
+                format!("member `{}` removed", old_member.name),
+            ));
+            continue;
+        };
+        if old_member.ty != new_member.ty {
+            changes.push(
+                ApiChange::new(
+                    ChangeKind::Members,
+                    item,
+                    format!("type of member `{}` changed", old_member.name),
+                )
+                .with_values(&old_member.ty, &new_member.ty),
+            );
+        }
+        if old_member.attributes != new_member.attributes {
+            changes.push(
+                ApiChange::new(
+                    ChangeKind::Layout,
+                    item,
+                    format!("attributes of member `{}` changed", old_member.name),
+                )
+                .with_values(
+                    old_member.attributes.join(" "),
+                    new_member.attributes.join(" "),
+                ),
+            );
+        }
+        if old_member.conditional != new_member.conditional {
+            changes.push(
+                ApiChange::new(
+                    ChangeKind::Conditional,
+                    item,
+                    format!(
+                        "member `{}` declared under different conditions",
+                        old_member.name
+                    ),
+                )
+                .with_values(
+                    old_member.conditional.as_deref().unwrap_or("always"),
+                    new_member.conditional.as_deref().unwrap_or("always"),
+                ),
+            );
+        }
+    }
+    for new_member in new {
+        if find(old, &new_member.name).is_none() {
+            changes.push(ApiChange::new(
+                ChangeKind::Members,
+                item,
+                format!("member `{}` added", new_member.name),
+            ));
+        }
+    }
+
+    // Members that are kept but appear in a different order change the memory layout
+    let order = |members: &[ApiMember], other: &[ApiMember]| {
+        members
+            .iter()
+            .filter(|member| find(other, &member.name).is_some())
+            .map(|member| member.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let (old_order, new_order) = (order(old, new), order(new, old));
+    if old_order != new_order {
+        changes.push(
+            ApiChange::new(ChangeKind::Layout, item, "members reordered".to_string())
+                .with_values(old_order.join(", "), new_order.join(", ")),
+        );
+    }
+}
+
+impl fmt::Display for ApiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Changes in {} from {} to {}",
+            self.package, self.old_version, self.new_version
+        )?;
+        if self.changes.is_empty() {
+            return writeln!(f, "  no API changes");
+        }
+        for change in &self.changes {
+            write!(
+                f,
+                "  {:<12} {}: {}",
+                change.kind.name(),
+                change.item(),
+                change.message
+            )?;
+            if let (Some(old), Some(new)) = (&change.old, &change.new) {
+                write!(f, " (`{old}` -> `{new}`)")?;
+            }
+            if change.breaking {
+                write!(f, " [breaking]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use crate::api::{Api, ApiInstance, ApiItem};
+use std::collections::HashMap;
+use wesldoc_ast::Version;
+
+/// The versions in which the items of a package were introduced and last changed, worked out
+/// from the APIs stored for all previously documented versions.
+#[derive(Debug, Default)]
+pub struct ItemHistory {
+    items: HashMap<String, ItemVersions>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemVersions {
+    /// The version the item was introduced in. `None` if it exists since the oldest documented
+    /// version, which is not worth a badge.
+    pub since: Option<Version>,
+    /// The last version the signature of the item changed in.
+    pub changed: Option<Version>,
+}
+
+impl ItemHistory {
+    /// `previous` are the APIs of older versions, sorted from oldest to newest.
+    pub fn new(previous: &[Api], current: &Api) -> Self {
+        let mut items = HashMap::<String, ItemVersions>::new();
+        let mut last: Option<&Api> = None;
+        for api in previous.iter().chain([current]) {
+            let mut next = HashMap::new();
+            for (key, item) in &api.items {
+                // Items that were removed and added again start over
+                let versions = match (last.and_then(|last| last.items.get(key)), items.remove(key))
+                {
+                    (Some(last_item), Some(mut versions)) => {
+                        if !same_signature(last_item, item) {
+                            versions.changed = Some(api.version.clone());
+                        }
+                        versions
+                    }
+                    _ => ItemVersions {
+                        since: last.map(|_| api.version.clone()),
+                        changed: None,
+                    },
+                };
+                next.insert(key.clone(), versions);
+            }
+            items = next;
+            last = Some(api);
+        }
+        Self { items }
+    }
+
+    /// The versions of an item by its API key, e.g. `fn lighting::shade`.
+    pub fn get(&self, key: &str) -> Option<&ItemVersions> {
+        self.items.get(key)
+    }
+
+    /// The versions of a module and its direct children, sorted by key.
+    pub fn module_entries(&self, module_path: &[&str]) -> Vec<(&str, &ItemVersions)> {
+        let module_path = module_path.join("::");
+        let mut entries = self
+            .items
+            .iter()
+            .filter(|(key, _)| {
+                let path = key.split_once(' ').map_or(key.as_str(), |(_, path)| path);
+                let parent = path.rsplit_once("::").map_or("", |(parent, _)| parent);
+                path == module_path || parent == module_path
+            })
+            .map(|(key, versions)| (key.as_str(), versions))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+        entries
+    }
+}
+
+/// Values, bindings and other attributes are not part of the signature.
+fn same_signature(a: &ApiItem, b: &ApiItem) -> bool {
+    let signature = |instance: &ApiInstance| {
+        (
+            instance.conditional.clone(),
+            instance.signature.clone(),
+            instance.members.clone(),
+        )
+    };
+    a.instances
+        .iter()
+        .map(signature)
+        .eq(b.instances.iter().map(signature))
+}
@@ -1,5 +1,6 @@
 use crate::Result;
 use askama::Template;
+use serde::{Deserialize, Serialize};
 use std::{
     fs::{self, File},
     path::Path,
@@ -7,8 +8,15 @@ use std::{
 
 pub fn update(base_path: &Path) -> Result<()> {
     let packages = get_packages(base_path)?;
+    let members = load_workspace_members(base_path)?;
+
+    // Workspace members are listed separately, all other packages are their dependencies
+    let (members, packages): (Vec<_>, Vec<_>) = packages
+        .into_iter()
+        .partition(|package| members.contains(package));
 
     let template = IndexTemplate {
+        members: &members,
         packages: &packages,
     };
     template.write_into(&mut File::create(base_path.join("index.html"))?)?;
@@ -16,6 +24,32 @@ pub fn update(base_path: &Path) -> Result<()> {
     Ok(())
 }
 
+pub fn set_workspace_members(base_path: &Path, members: &[String]) -> Result<()> {
+    fs::write(
+        base_path.join(WORKSPACE_FILE),
+        serde_json::to_string_pretty(&WorkspaceFile {
+            members: members.to_vec(),
+        })?,
+    )?;
+    update(base_path)
+}
+
+const WORKSPACE_FILE: &str = "workspace.json";
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceFile {
+    members: Vec<String>,
+}
+
+fn load_workspace_members(base_path: &Path) -> Result<Vec<String>> {
+    let path = base_path.join(WORKSPACE_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let file = serde_json::from_str::<WorkspaceFile>(&fs::read_to_string(path)?)?;
+    Ok(file.members)
+}
+
 fn get_packages(base_path: &Path) -> Result<Vec<String>> {
     let mut packages = Vec::new();
 
@@ -39,5 +73,6 @@ fn get_packages(base_path: &Path) -> Result<Vec<String>> {
 #[derive(Template)]
 #[template(path = "index.html")]
 struct IndexTemplate<'a> {
+    members: &'a [String],
     packages: &'a [String],
 }
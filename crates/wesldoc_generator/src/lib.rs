@@ -1,15 +1,23 @@
 mod all_items;
+pub mod api;
+mod cache;
 mod context;
+pub mod diff;
+mod history;
 mod index;
 mod render;
 mod static_files;
+pub mod versions;
 
-use crate::{context::Context, render::*};
+use crate::{
+    api::Api, cache::OutputTree, context::Context, diff::ApiDiff, history::ItemHistory, render::*,
+};
 use askama::Template;
+use rayon::prelude::*;
 use serde_json::Value;
 use std::{
     cmp::Ordering,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::{self, File},
     path::Path,
 };
@@ -24,9 +32,26 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("serialization error")]
     Template(#[from] serde_json::Error),
+    #[error("package {0} is not documented")]
+    UnknownPackage(String),
+    #[error("version {version} of {package} is not documented")]
+    UnknownVersion { package: String, version: String },
+    #[error("invalid version alias {0:?}")]
+    InvalidAlias(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GenerateOptions {
+    /// Root URLs of dependencies whose docs are hosted elsewhere, keyed by package name. A root
+    /// URL points to the directory that contains the package's docs, e.g.
+    /// `https://example.com/docs/math_utils/1.0.0` for `math_utils` version `1.0.0`.
+    pub extern_html_root_urls: HashMap<String, String>,
+    /// A fingerprint of the sources and options the docs are compiled from. It is stored with
+    /// the docs, so later runs can skip unchanged packages with [`is_up_to_date`].
+    pub source_fingerprint: Option<String>,
 }
 
-pub fn generate(docs: &WeslDocs, base_path: &Path) -> Result<()> {
+pub fn generate(docs: &WeslDocs, base_path: &Path, options: &GenerateOptions) -> Result<()> {
     // Write static files
     static_files::write_static_files(base_path)?;
 
@@ -37,13 +62,6 @@ pub fn generate(docs: &WeslDocs, base_path: &Path) -> Result<()> {
 
     // Versions
     let existing_versions = existing_versions(&base_path)?;
-    let is_latest =
-        existing_versions
-            .iter()
-            .all(|version| match docs.version.cmp_precedence(version) {
-                Ordering::Equal | Ordering::Greater => true,
-                Ordering::Less => false,
-            });
     let all_versions = {
         let mut versions = existing_versions.clone();
         versions.insert(docs.version.clone());
@@ -53,21 +71,49 @@ pub fn generate(docs: &WeslDocs, base_path: &Path) -> Result<()> {
 
         versions
     };
+    let mut common = load_common_json(&base_path)?;
+    let is_latest = versions::latest_version(&all_versions, &versions::versions(&common, "yanked"))
+        == Some(&docs.version);
+
+    // Diff against the previous version and collect the history of all items
+    let api = Api::of(docs);
+    let previous_apis = previous_apis(&base_path, &docs.version, &existing_versions)?;
+    let diff = previous_apis
+        .last()
+        .map(|previous| ApiDiff::between(previous, &api));
+    let history = ItemHistory::new(&previous_apis, &api);
 
     // Gen docs
-    gen_doc(docs, false, &base_path)?;
+    let api = GeneratedApi {
+        api: &api,
+        diff: diff.as_ref(),
+        history: &history,
+    };
+    gen_doc(docs, &api, false, &base_path, options)?;
     if is_latest {
-        gen_doc(docs, true, &base_path)?;
+        gen_doc(docs, &api, true, &base_path, options)?;
     }
 
     // Store versions
-    let mut common = load_common_json(&base_path)?;
     common["versions"] = Value::Array(
         all_versions
             .iter()
             .map(|version| Value::String(version.to_string()))
             .collect(),
     );
+    match &diff {
+        Some(diff) => {
+            if !common["changes"].is_object() {
+                common["changes"] = Value::Object(Default::default());
+            }
+            common["changes"][docs.version.to_string()] = Value::String(diff.old_version.clone());
+        }
+        None => {
+            if let Some(changes) = common.get_mut("changes").and_then(Value::as_object_mut) {
+                changes.remove(&docs.version.to_string());
+            }
+        }
+    }
     store_common_json(&base_path, &common)?;
 
     // Update index
@@ -76,6 +122,28 @@ pub fn generate(docs: &WeslDocs, base_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Renders the changes between two versions as a standalone HTML page.
+pub fn diff_html(diff: &ApiDiff) -> String {
+    DiffTemplate { diff }.to_string()
+}
+
+/// Whether the docs of `package` at `version` in `base_path` were generated from sources with
+/// this fingerprint, see [`GenerateOptions::source_fingerprint`].
+pub fn is_up_to_date(
+    base_path: &Path,
+    package: &str,
+    version: &Version,
+    fingerprint: &str,
+) -> bool {
+    cache::is_up_to_date(base_path, package, version, fingerprint)
+}
+
+/// Lists `members` as workspace members on the top-level index page in `base_path`. All other
+/// documented packages are listed as their dependencies.
+pub fn set_workspace_members(base_path: &Path, members: &[String]) -> Result<()> {
+    index::set_workspace_members(base_path, members)
+}
+
 fn load_common_json(base_path: &Path) -> Result<Value> {
     let common_path = base_path.join("common.js");
     let source = if common_path.exists() {
@@ -119,39 +187,114 @@ fn existing_versions(path: &Path) -> Result<HashSet<Version>> {
     Ok(versions)
 }
 
-fn gen_doc(doc: &WeslDocs, build_as_latest: bool, base_path: &Path) -> Result<()> {
-    let base_path = if build_as_latest {
-        base_path.join("latest")
-    } else {
-        base_path.join(doc.version.to_string())
-    };
+/// The APIs of all documented versions below `version`, sorted from oldest to newest. Versions
+/// documented without an `api.json` are skipped.
+fn previous_apis(
+    base_path: &Path,
+    version: &Version,
+    existing_versions: &HashSet<Version>,
+) -> Result<Vec<Api>> {
+    let mut versions = existing_versions
+        .iter()
+        .filter(|existing| existing.cmp_precedence(version) == Ordering::Less)
+        .collect::<Vec<_>>();
+    versions.sort_by(|a, b| a.cmp_precedence(b));
+
+    let mut apis = Vec::new();
+    for previous in versions {
+        let path = base_path.join(previous.to_string()).join("api.json");
+        if path.is_file() {
+            apis.push(Api::load(&path)?);
+        }
+    }
+    Ok(apis)
+}
+
+/// The API of the documented version and what is derived from the previous versions.
+struct GeneratedApi<'a> {
+    api: &'a Api,
+    diff: Option<&'a ApiDiff>,
+    history: &'a ItemHistory,
+}
+
+fn gen_doc(
+    doc: &WeslDocs,
+    api: &GeneratedApi,
+    build_as_latest: bool,
+    base_path: &Path,
+    options: &GenerateOptions,
+) -> Result<()> {
+    let mut output = OutputTree::create(
+        match build_as_latest {
+            true => base_path.join("latest"),
+            false => base_path.join(doc.version.to_string()),
+        },
+        doc,
+        options,
+    )?;
+    let base_path = output.tmp_path().to_path_buf();
     let base_path_docs = base_path.join(&doc.root.name);
     let base_path_src = base_path.join("src").join(&doc.root.name);
 
     // Prepare directories
-    fs::remove_dir_all(&base_path).ok();
     fs::create_dir_all(&base_path_docs)?;
     fs::create_dir_all(&base_path_src)?;
 
-    // Gen modules
-    gen_module(
-        &Context::new(build_as_latest, doc),
-        &base_path_docs,
-        &base_path_src,
-    )?;
+    // Gen modules, pages of unchanged modules are reused
+    let ctx = Context::new(doc, options, api.history);
+    gen_module(&ctx, &base_path_docs, &base_path_src, &mut output)?;
+
+    // Gen changes
+    if let Some(diff) = api.diff {
+        let template = ChangesTemplate {
+            ctx: &ctx,
+            title: "Changes",
+            diff,
+        };
+        template.write_into(&mut File::create(base_path_docs.join("changes.html"))?)?;
+    }
 
     // Store items
-    let items = all_items::all_items(doc);
+    let items = all_items::all_items(doc, options);
     let source = format!(
         "window.DOCS_ITEMS = {};\n",
         serde_json::ser::to_string(&items)?
     );
     fs::write(base_path.join("items.js"), source)?;
 
-    Ok(())
+    // Store api
+    api.api.store(&base_path.join("api.json"))?;
+
+    // Store the version the pages are shown as
+    let version = match build_as_latest {
+        true => "latest".to_string(),
+        false => doc.version.to_string(),
+    };
+    versions::write_version_script(&base_path, &version)?;
+
+    output.finish()
 }
 
-fn gen_module(ctx: &Context, base_path_docs: &Path, base_path_src: &Path) -> Result<()> {
+type Page<'a> = Box<dyn Fn() -> Result<()> + Sync + 'a>;
+
+/// Renders pages on all available cores and returns the first error.
+fn render_pages(pages: &[Page]) -> Result<()> {
+    pages
+        .par_iter()
+        .map(|page| page())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn gen_module(
+    ctx: &Context,
+    base_path_docs: &Path,
+    base_path_src: &Path,
+    output: &mut OutputTree,
+) -> Result<()> {
+    let reuse = output.is_unchanged(ctx);
+
     if let Some(source) = &ctx.module.source {
         let template = SourceTemplate {
             ctx,
@@ -161,14 +304,111 @@ fn gen_module(ctx: &Context, base_path_docs: &Path, base_path_src: &Path) -> Res
         };
         let mut path = base_path_src.to_path_buf();
         path.set_extension("html");
-        template.write_into(&mut File::create(path)?)?;
+        output.write_page(&path, reuse, &template)?;
     }
 
     let template = OverviewTemplate {
         ctx,
         title: &ctx.module.name,
     };
-    template.write_into(&mut File::create(base_path_docs.join("index.html"))?)?;
+    output.write_page(&base_path_docs.join("index.html"), reuse, &template)?;
+
+    // Item pages are rendered in parallel
+    {
+        let output = &*output;
+        let mut pages: Vec<Page> = Vec::new();
+        for (name, item) in &ctx.module.constants {
+            pages.push(Box::new(move || {
+                let ctx = ctx.with_item(name.to_string(), ItemKind::Constant);
+                let template = ConstantTemplate {
+                    ctx: &ctx,
+                    title: &name.to_string(),
+                    constants: &item.instances,
+                };
+                output.write_page(
+                    &base_path_docs.join(format!("const.{name}.html")),
+                    reuse,
+                    &template,
+                )
+            }));
+        }
+        for (name, item) in &ctx.module.overrides {
+            pages.push(Box::new(move || {
+                let ctx = ctx.with_item(name.to_string(), ItemKind::Override);
+                let template = OverrideTemplate {
+                    ctx: &ctx,
+                    title: &name.to_string(),
+                    overrides: &item.instances,
+                };
+                output.write_page(
+                    &base_path_docs.join(format!("override.{name}.html")),
+                    reuse,
+                    &template,
+                )
+            }));
+        }
+        for (name, item) in &ctx.module.global_variables {
+            pages.push(Box::new(move || {
+                let ctx = ctx.with_item(name.to_string(), ItemKind::GlobalVariable);
+                let template = GlobalVariableTemplate {
+                    ctx: &ctx,
+                    title: &name.to_string(),
+                    variables: &item.instances,
+                };
+                output.write_page(
+                    &base_path_docs.join(format!("var.{name}.html")),
+                    reuse,
+                    &template,
+                )
+            }));
+        }
+        for (name, item) in &ctx.module.structs {
+            pages.push(Box::new(move || {
+                let ctx = ctx.with_item(name.to_string(), ItemKind::Struct);
+                let template = StructTemplate {
+                    ctx: &ctx,
+                    title: &name.to_string(),
+                    structs: &item.instances,
+                };
+                output.write_page(
+                    &base_path_docs.join(format!("struct.{name}.html")),
+                    reuse,
+                    &template,
+                )
+            }));
+        }
+        for (name, item) in &ctx.module.functions {
+            pages.push(Box::new(move || {
+                let ctx = ctx.with_item(name.to_string(), ItemKind::Function);
+                let template = FunctionTemplate {
+                    ctx: &ctx,
+                    title: &name.to_string(),
+                    functions: &item.instances,
+                };
+                output.write_page(
+                    &base_path_docs.join(format!("fn.{name}.html")),
+                    reuse,
+                    &template,
+                )
+            }));
+        }
+        for (name, item) in &ctx.module.type_aliases {
+            pages.push(Box::new(move || {
+                let ctx = ctx.with_item(name.to_string(), ItemKind::TypeAlias);
+                let template = TypeAliasTemplate {
+                    ctx: &ctx,
+                    title: &name.to_string(),
+                    type_aliases: &item.instances,
+                };
+                output.write_page(
+                    &base_path_docs.join(format!("alias.{name}.html")),
+                    reuse,
+                    &template,
+                )
+            }));
+        }
+        render_pages(&pages)?;
+    }
 
     for module in &ctx.module.modules {
         let ctx = ctx.with_submodule(module);
@@ -179,79 +419,7 @@ fn gen_module(ctx: &Context, base_path_docs: &Path, base_path_src: &Path) -> Res
         let base_path_src = base_path_src.join(&module.name);
         fs::create_dir(&base_path_src)?;
 
-        gen_module(&ctx, &base_path_docs, &base_path_src)?;
-    }
-
-    for (name, item) in &ctx.module.constants {
-        let ctx = ctx.with_item(name.to_string(), ItemKind::Constant);
-        let template = ConstantTemplate {
-            ctx: &ctx,
-            title: &name.to_string(),
-            constants: &item.instances,
-        };
-        template.write_into(&mut File::create(
-            base_path_docs.join(format!("const.{name}.html")),
-        )?)?;
-    }
-
-    for (name, item) in &ctx.module.overrides {
-        let ctx = ctx.with_item(name.to_string(), ItemKind::Override);
-        let template = OverrideTemplate {
-            ctx: &ctx,
-            title: &name.to_string(),
-            overrides: &item.instances,
-        };
-        template.write_into(&mut File::create(
-            base_path_docs.join(format!("override.{name}.html")),
-        )?)?;
-    }
-
-    for (name, item) in &ctx.module.global_variables {
-        let ctx = ctx.with_item(name.to_string(), ItemKind::GlobalVariable);
-        let template = GlobalVariableTemplate {
-            ctx: &ctx,
-            title: &name.to_string(),
-            variables: &item.instances,
-        };
-        template.write_into(&mut File::create(
-            base_path_docs.join(format!("var.{name}.html")),
-        )?)?;
-    }
-
-    for (name, item) in &ctx.module.structs {
-        let ctx = ctx.with_item(name.to_string(), ItemKind::Struct);
-        let template = StructTemplate {
-            ctx: &ctx,
-            title: &name.to_string(),
-            structs: &item.instances,
-        };
-        template.write_into(&mut File::create(
-            base_path_docs.join(format!("struct.{name}.html")),
-        )?)?;
-    }
-
-    for (name, item) in &ctx.module.functions {
-        let ctx = ctx.with_item(name.to_string(), ItemKind::Function);
-        let template = FunctionTemplate {
-            ctx: &ctx,
-            title: &name.to_string(),
-            functions: &item.instances,
-        };
-        template.write_into(&mut File::create(
-            base_path_docs.join(format!("fn.{name}.html")),
-        )?)?;
-    }
-
-    for (name, item) in &ctx.module.type_aliases {
-        let ctx = ctx.with_item(name.to_string(), ItemKind::TypeAlias);
-        let template = TypeAliasTemplate {
-            ctx: &ctx,
-            title: &name.to_string(),
-            type_aliases: &item.instances,
-        };
-        template.write_into(&mut File::create(
-            base_path_docs.join(format!("alias.{name}.html")),
-        )?)?;
+        gen_module(&ctx, &base_path_docs, &base_path_src, output)?;
     }
 
     Ok(())
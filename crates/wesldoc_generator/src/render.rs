@@ -1,9 +1,9 @@
-use crate::context::Context;
+use crate::{context::Context, diff::ApiDiff};
 use askama::Template;
 use wesldoc_ast::{
-    Attribute, BuiltinValue, ConservativeDepth, Constant, DiagnosticSeverity, Expression, Function,
-    GlobalVariable, InterpolationSampling, InterpolationType, ItemKind, Override, Struct,
-    TypeAlias, TypeExpression,
+    Attribute, BuiltinValue, ConservativeDepth, Constant, DefinitionPath, DiagnosticSeverity,
+    DirectiveKind, Expression, Function, GlobalVariable, InterpolationSampling, InterpolationType,
+    ItemKind, Override, ReExport, Struct, TypeAlias, TypeExpression,
 };
 
 #[derive(Template)]
@@ -71,6 +71,28 @@ pub struct TypeAliasTemplate<'a> {
     pub type_aliases: &'a [TypeAlias],
 }
 
+#[derive(Template)]
+#[template(path = "changes.html")]
+pub struct ChangesTemplate<'a> {
+    pub ctx: &'a Context<'a>,
+    pub title: &'a str,
+    pub diff: &'a ApiDiff,
+}
+
+/// Sets the version a documented tree is shown as, its own version or `latest`. This is the only
+/// file that differs between a version and `latest`.
+#[derive(Template)]
+#[template(path = "version.js", escape = "none")]
+pub struct VersionTemplate<'a> {
+    pub version: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "diff.html")]
+pub struct DiffTemplate<'a> {
+    pub diff: &'a ApiDiff,
+}
+
 #[derive(Template)]
 #[template(path = "render_type.html")]
 pub struct RenderTypeTemplate<'a> {
@@ -116,7 +138,24 @@ fn path_item_class(kind: &ItemKind, last: &bool) -> &'static str {
     if *last { item_class(kind) } else { "path" }
 }
 
-fn builtin_str(builtin: &BuiltinValue) -> &'static str {
+fn re_export_path(re_export: &ReExport) -> String {
+    let mut path = match &re_export.def_path {
+        DefinitionPath::Absolute(components) => ["package"]
+            .into_iter()
+            .chain(components.iter().map(|c| c.as_str()))
+            .collect::<Vec<_>>(),
+        DefinitionPath::Package(package, _, components) => [package.as_str()]
+            .into_iter()
+            .chain(components.iter().map(|c| c.as_str()))
+            .collect::<Vec<_>>(),
+    };
+    if re_export.kind != ItemKind::Module {
+        path.push(&re_export.original_name.0);
+    }
+    path.join("::")
+}
+
+pub(crate) fn builtin_str(builtin: &BuiltinValue) -> &'static str {
     match builtin {
         BuiltinValue::VertexIndex => "vertex_index",
         BuiltinValue::InstanceIndex => "instance_index",
@@ -149,7 +188,17 @@ fn builtin_str(builtin: &BuiltinValue) -> &'static str {
     }
 }
 
-fn severity_str(diagnostic: &DiagnosticSeverity) -> &'static str {
+fn directive_str(directive: &DirectiveKind) -> String {
+    match directive {
+        DirectiveKind::Enable(extensions) => format!("enable {};", extensions.join(", ")),
+        DirectiveKind::Requires(extensions) => format!("requires {};", extensions.join(", ")),
+        DirectiveKind::Diagnostic { severity, rule } => {
+            format!("diagnostic({}, {rule});", severity_str(severity))
+        }
+    }
+}
+
+pub(crate) fn severity_str(diagnostic: &DiagnosticSeverity) -> &'static str {
     match diagnostic {
         DiagnosticSeverity::Error => "error",
         DiagnosticSeverity::Warning => "warning",
@@ -158,7 +207,7 @@ fn severity_str(diagnostic: &DiagnosticSeverity) -> &'static str {
     }
 }
 
-fn interpolation_str(interpolation: &InterpolationType) -> &'static str {
+pub(crate) fn interpolation_str(interpolation: &InterpolationType) -> &'static str {
     match interpolation {
         InterpolationType::Perspective => "perspective",
         InterpolationType::Linear => "linear",
@@ -166,7 +215,7 @@ fn interpolation_str(interpolation: &InterpolationType) -> &'static str {
     }
 }
 
-fn sampling_str(sampling: &InterpolationSampling) -> &'static str {
+pub(crate) fn sampling_str(sampling: &InterpolationSampling) -> &'static str {
     match sampling {
         InterpolationSampling::Center => "center",
         InterpolationSampling::Centroid => "centroid",
@@ -176,7 +225,7 @@ fn sampling_str(sampling: &InterpolationSampling) -> &'static str {
     }
 }
 
-fn conservative_depth_str(conservative_depth: &ConservativeDepth) -> &'static str {
+pub(crate) fn conservative_depth_str(conservative_depth: &ConservativeDepth) -> &'static str {
     match conservative_depth {
         ConservativeDepth::GreaterEqual => "greater_equal",
         ConservativeDepth::LessEqual => "less_equal",
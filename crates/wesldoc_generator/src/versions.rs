@@ -0,0 +1,269 @@
+use crate::{
+    Error, Result, api::Api, index, load_common_json, render::VersionTemplate, store_common_json,
+};
+use askama::Template;
+use serde_json::Value;
+use std::{
+    fs::{self, File},
+    path::Path,
+};
+use wesldoc_ast::Version;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionInfo {
+    pub version: Version,
+    /// Whether `latest` points to this version.
+    pub latest: bool,
+    pub yanked: bool,
+    pub aliases: Vec<String>,
+}
+
+impl VersionInfo {
+    pub fn is_prerelease(&self) -> bool {
+        !self.version.pre.is_empty()
+    }
+}
+
+/// Lists the documented versions of a package, newest first.
+pub fn list_versions(base_path: &Path, package: &str) -> Result<Vec<VersionInfo>> {
+    let common = load_common_json(&package_path(base_path, package)?)?;
+    let yanked = versions(&common, "yanked");
+    let all_versions = versions(&common, "versions");
+    let latest = latest_version(&all_versions, &yanked);
+
+    Ok(all_versions
+        .iter()
+        .map(|version| VersionInfo {
+            version: version.clone(),
+            latest: latest == Some(version),
+            yanked: yanked.contains(version),
+            aliases: aliases(&common)
+                .filter(|(_, target)| *target == version.to_string())
+                .map(|(alias, _)| alias.to_string())
+                .collect(),
+        })
+        .collect())
+}
+
+/// Removes the docs of a version. The whole package is removed with its last version.
+pub fn remove_version(base_path: &Path, package: &str, version: &Version) -> Result<()> {
+    let package_path = package_path(base_path, package)?;
+    let mut common = load_common_json(&package_path)?;
+    let version_path = package_path.join(version.to_string());
+    if !version_path.is_dir() {
+        return Err(unknown_version(package, version));
+    }
+    fs::remove_dir_all(version_path)?;
+
+    let all_versions = versions(&common, "versions")
+        .into_iter()
+        .filter(|v| v != version)
+        .collect::<Vec<_>>();
+    if all_versions.is_empty() {
+        fs::remove_dir_all(&package_path)?;
+        return index::update(base_path);
+    }
+
+    set_versions(&mut common, "versions", &all_versions);
+    let yanked = versions(&common, "yanked")
+        .into_iter()
+        .filter(|v| v != version)
+        .collect::<Vec<_>>();
+    set_versions(&mut common, "yanked", &yanked);
+    if let Some(aliases) = common.get_mut("aliases").and_then(Value::as_object_mut) {
+        aliases.retain(|_, target| *target != version.to_string());
+    }
+    if let Some(changes) = common.get_mut("changes").and_then(Value::as_object_mut) {
+        changes.remove(&version.to_string());
+    }
+    remove_if_empty(&mut common, "aliases");
+    remove_if_empty(&mut common, "changes");
+    store_common_json(&package_path, &common)?;
+
+    update_latest(&package_path, &common)
+}
+
+/// Marks a version as yanked, or not yanked anymore. Yanked versions stay available, but
+/// `latest` does not point to them.
+pub fn yank_version(base_path: &Path, package: &str, version: &Version, yank: bool) -> Result<()> {
+    let package_path = package_path(base_path, package)?;
+    let mut common = load_common_json(&package_path)?;
+    if !versions(&common, "versions").contains(version) {
+        return Err(unknown_version(package, version));
+    }
+
+    let mut yanked = versions(&common, "yanked")
+        .into_iter()
+        .filter(|v| v != version)
+        .collect::<Vec<_>>();
+    if yank {
+        yanked.push(version.clone());
+        yanked.sort_by(|a, b| a.cmp_precedence(b).reverse());
+    }
+    set_versions(&mut common, "yanked", &yanked);
+    store_common_json(&package_path, &common)?;
+
+    update_latest(&package_path, &common)
+}
+
+/// Points an alias such as `next` or `dev` to a version, or removes it if `version` is `None`.
+pub fn set_alias(
+    base_path: &Path,
+    package: &str,
+    alias: &str,
+    version: Option<&Version>,
+) -> Result<()> {
+    if alias == "latest"
+        || Version::parse(alias).is_ok()
+        || alias.is_empty()
+        || !alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::InvalidAlias(alias.to_string()));
+    }
+
+    let package_path = package_path(base_path, package)?;
+    let mut common = load_common_json(&package_path)?;
+    match version {
+        Some(version) => {
+            if !versions(&common, "versions").contains(version) {
+                return Err(unknown_version(package, version));
+            }
+            if !common["aliases"].is_object() {
+                common["aliases"] = Value::Object(Default::default());
+            }
+            common["aliases"][alias] = Value::String(version.to_string());
+        }
+        None => {
+            if let Some(aliases) = common.get_mut("aliases").and_then(Value::as_object_mut) {
+                aliases.remove(alias);
+            }
+            remove_if_empty(&mut common, "aliases");
+        }
+    }
+    store_common_json(&package_path, &common)
+}
+
+/// The version `latest` points to: the newest stable release that is not yanked. Pre-releases
+/// and yanked versions are only used if there is nothing else. `versions` are sorted newest
+/// first.
+pub(crate) fn latest_version<'a>(
+    versions: &'a [Version],
+    yanked: &[Version],
+) -> Option<&'a Version> {
+    let available = || versions.iter().filter(|v| !yanked.contains(v));
+    available()
+        .find(|v| v.pre.is_empty())
+        .or_else(|| available().next())
+        .or_else(|| versions.first())
+}
+
+/// Versions stored as an array of strings in `common.js`.
+pub(crate) fn versions(common: &Value, key: &str) -> Vec<Version> {
+    common[key]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|version| Version::parse(version.as_str()?).ok())
+        .collect()
+}
+
+fn set_versions(common: &mut Value, key: &str, versions: &[Version]) {
+    common[key] = Value::Array(
+        versions
+            .iter()
+            .map(|version| Value::String(version.to_string()))
+            .collect(),
+    );
+    if key != "versions" {
+        remove_if_empty(common, key);
+    }
+}
+
+fn aliases(common: &Value) -> impl Iterator<Item = (&str, &str)> {
+    common["aliases"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(alias, version)| Some((alias.as_str(), version.as_str()?)))
+}
+
+/// Optional entries are left out of `common.js` while they are empty.
+fn remove_if_empty(common: &mut Value, key: &str) {
+    let is_empty = match &common[key] {
+        Value::Array(array) => array.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        Value::Null => true,
+        _ => false,
+    };
+    if is_empty && let Some(common) = common.as_object_mut() {
+        common.remove(key);
+    }
+}
+
+fn package_path(base_path: &Path, package: &str) -> Result<std::path::PathBuf> {
+    let package_path = base_path.join(package);
+    match package_path.join("common.js").is_file() {
+        true => Ok(package_path),
+        false => Err(Error::UnknownPackage(package.to_string())),
+    }
+}
+
+fn unknown_version(package: &str, version: &Version) -> Error {
+    Error::UnknownVersion {
+        package: package.to_string(),
+        version: version.to_string(),
+    }
+}
+
+/// Points `latest` to the version it should point to after versions were removed or yanked.
+fn update_latest(package_path: &Path, common: &Value) -> Result<()> {
+    let all_versions = versions(common, "versions");
+    let Some(target) = latest_version(&all_versions, &versions(common, "yanked")) else {
+        return Ok(());
+    };
+
+    let latest_api = package_path.join("latest").join("api.json");
+    if latest_api.is_file() && Api::load(&latest_api)?.version == *target {
+        return Ok(());
+    }
+
+    copy_as_latest(package_path, target)
+}
+
+/// Copies the docs of a documented version to `latest`. Pages are the same for all versions, they
+/// read the version they are shown as from `version.js`, which is rendered for `latest`. The copy
+/// is moved into place once it is complete.
+fn copy_as_latest(package_path: &Path, version: &Version) -> Result<()> {
+    let tmp = package_path.join(".latest.tmp");
+    fs::remove_dir_all(&tmp).ok();
+    copy_dir(&package_path.join(version.to_string()), &tmp)?;
+    write_version_script(&tmp, "latest")?;
+
+    let latest = package_path.join("latest");
+    fs::remove_dir_all(&latest).ok();
+    fs::rename(&tmp, &latest)?;
+
+    Ok(())
+}
+
+/// Writes `version.js` into a documented tree, which sets the version its pages are shown as.
+pub(crate) fn write_version_script(tree: &Path, version: &str) -> Result<()> {
+    VersionTemplate { version }.write_into(&mut File::create(tree.join("version.js"))?)?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
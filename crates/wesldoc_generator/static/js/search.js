@@ -143,7 +143,8 @@ function createItemElement(item) {
     secondDivElement.innerHTML = item.comment;
 
     var linkElement = document.createElement("a");
-    linkElement.href = window.DOCS_THIS_PACKAGE.root + item.url;
+    // Re-exports of packages hosted elsewhere link to absolute URLs
+    linkElement.href = /^[a-z]+:\/\//i.test(item.url) ? item.url : window.DOCS_THIS_PACKAGE.root + item.url;
     linkElement.appendChild(firstDivElement);
     linkElement.appendChild(secondDivElement);
 
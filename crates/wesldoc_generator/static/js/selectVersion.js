@@ -1,20 +1,81 @@
+var yanked = window.DOCS_COMMON.yanked || [];
+var aliases = window.DOCS_COMMON.aliases || {};
+
 document.getElementById("selectVersion").innerHTML = "";
-addVersion("latest");
+addVersion("latest", `latest (${latestVersion()})`);
+for (var alias in aliases) {
+    addVersion(alias, `${alias} (${aliases[alias]})`, aliases[alias]);
+}
 for (var i = 0; i < window.DOCS_COMMON.versions.length; i++) {
-    addVersion(window.DOCS_COMMON.versions[i]);
+    addVersion(window.DOCS_COMMON.versions[i], versionLabel(window.DOCS_COMMON.versions[i]));
 }
+addChanges();
+addYankedBanner();
 
-function addVersion(version) {
+function addVersion(version, label, target) {
     var select = document.getElementById("selectVersion");
     var base = window.DOCS_THIS_PACKAGE.root + "../";
 
     var option = document.createElement("option");
-    option.selected = version == window.DOCS_THIS_PACKAGE.version;
-    option.value = base + version + `/${window.DOCS_THIS_PACKAGE.name}/index.html`;
-    option.innerText = version;
+    option.selected = !target && version == window.DOCS_THIS_PACKAGE.version;
+    option.value = base + (target || version) + `/${window.DOCS_THIS_PACKAGE.name}/index.html`;
+    option.innerText = label;
     select.appendChild(option);
 }
 
+function versionLabel(version) {
+    var label = version;
+    if (isPrerelease(version)) {
+        label += " (pre-release)";
+    }
+    if (yanked.includes(version)) {
+        label += " (yanked)";
+    }
+    return label;
+}
+
+function isPrerelease(version) {
+    return version.split("+")[0].includes("-");
+}
+
+// Same rule as the generator: the newest stable version that is not yanked
+function latestVersion() {
+    var versions = window.DOCS_COMMON.versions;
+    var available = versions.filter((version) => !yanked.includes(version));
+    return available.find((version) => !isPrerelease(version)) || available[0] || versions[0];
+}
+
+function addChanges() {
+    var thisVersion = window.DOCS_THIS_PACKAGE.version;
+    var version = thisVersion == "latest" ? latestVersion() : thisVersion;
+    var previous = (window.DOCS_COMMON.changes || {})[version];
+    if (!previous) {
+        return;
+    }
+
+    var select = document.getElementById("selectVersion");
+    var base = window.DOCS_THIS_PACKAGE.root + "../";
+
+    var option = document.createElement("option");
+    option.selected = location.pathname.endsWith("/changes.html");
+    option.value = base + thisVersion + `/${window.DOCS_THIS_PACKAGE.name}/changes.html`;
+    option.innerText = `Changes since ${previous}`;
+    select.appendChild(option);
+}
+
+function addYankedBanner() {
+    var version = window.DOCS_THIS_PACKAGE.version;
+    if (!yanked.includes(version)) {
+        return;
+    }
+
+    var banner = document.createElement("div");
+    banner.className = "error-banner";
+    banner.innerText = `Version ${version} has been yanked.`;
+    var content = document.getElementById("innerContent");
+    content.insertBefore(banner, content.firstChild);
+}
+
 function changeVersion() {
     var value = document.getElementById("selectVersion").value;
     location.href = value;
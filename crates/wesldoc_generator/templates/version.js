@@ -0,0 +1 @@
+window.DOCS_THIS_VERSION = "{{ version }}";
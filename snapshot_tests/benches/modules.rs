@@ -0,0 +1,45 @@
+//! Documents a synthetic package with hundreds of modules that all import the same shared
+//! modules and a dependency. Every module is read and parsed once per run, so the time grows
+//! linearly with the number of modules instead of with the number of imports.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use snapshot_tests::synthetic_package;
+use std::path::Path;
+use wesldoc::{Args, Parser};
+
+fn check(path: &Path, args: &[&str]) {
+    Args::parse_from(
+        ["wesldoc", "check", path.to_str().unwrap()]
+            .into_iter()
+            .chain(args.iter().copied()),
+    )
+    .run()
+    .unwrap();
+}
+
+fn modules(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modules");
+    group.sample_size(10);
+    for modules in [100, 400] {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        synthetic_package(tmp_dir.path(), modules);
+        let app = tmp_dir.path().join("app");
+
+        group.bench_with_input(BenchmarkId::new("compile", modules), &app, |b, app| {
+            b.iter(|| check(app, &[]))
+        });
+        // Baseline that reads and parses a module every time it is imported
+        group.bench_with_input(
+            BenchmarkId::new("compile_uncached", modules),
+            &app,
+            |b, app| b.iter(|| check(app, &["--no-module-cache"])),
+        );
+        group.bench_with_input(BenchmarkId::new("parse_only", modules), &app, |b, app| {
+            b.iter(|| check(app, &["--parse-only"]))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, modules);
+criterion_main!(benches);
@@ -1 +1,46 @@
+//! Helpers shared by the tests and benchmarks.
 
+use std::{fs, path::Path};
+
+pub const DEPENDENCY_MODULES: usize = 20;
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+/// Writes a package `app` with `modules` modules and its dependency `shapes` to `dir`. All
+/// modules import the same shared module and modules of the dependency. The shared module has a
+/// `const_assert` and re-exports an item of the dependency.
+pub fn synthetic_package(dir: &Path, modules: usize) {
+    write(
+        &dir.join("shapes/wesl.toml"),
+        "[package]\nname = \"shapes\"\nversion = \"1.0.0\"\nedition = \"unstable_2025\"\nroot = \"shaders\"\n",
+    );
+    for idx in 0..DEPENDENCY_MODULES {
+        write(
+            &dir.join(format!("shapes/shaders/shape_{idx}.wesl")),
+            &format!(
+                "/// A shape.\nstruct Shape{idx} {{\n    size: f32,\n}}\n\n/// Scales a shape.\nfn scale_{idx}(shape: Shape{idx}, factor: f32) -> Shape{idx} {{\n    return Shape{idx}(shape.size * factor);\n}}\n"
+            ),
+        );
+    }
+
+    write(
+        &dir.join("app/wesl.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"unstable_2025\"\nroot = \"shaders\"\n\n[dependencies]\nshapes = { path = \"../shapes\" }\n",
+    );
+    write(
+        &dir.join("app/shaders/common.wesl"),
+        "@publish import shapes::shape_0::Shape0;\n\n/// Shared settings.\nstruct Settings {\n    scale: f32,\n}\n\n/// The default scale.\nconst SCALE = 1.0;\n\nconst_assert SCALE > 0.0;\n\n/// Returns the default settings.\nfn settings() -> Settings {\n    return Settings(SCALE);\n}\n",
+    );
+    for idx in 0..modules {
+        let shape = idx % DEPENDENCY_MODULES;
+        write(
+            &dir.join(format!("app/shaders/group_{}/module_{idx}.wesl", idx / 50)),
+            &format!(
+                "import package::common::{{Settings, settings}};\nimport shapes::shape_{shape}::{{Shape{shape}, scale_{shape}}};\n\n/// Scales a shape by the default settings.\nfn apply_{idx}(shape: Shape{shape}) -> Shape{shape} {{\n    let s: Settings = settings();\n    return scale_{shape}(shape, s.scale);\n}}\n"
+            ),
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use std::fs;
+use wesldoc::{Args, Parser};
+
+#[test]
+fn broken_module_is_documented() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/broken_module",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+    ])
+    .run()?;
+
+    let base = tmp_dir.path().join("broken_module/latest");
+
+    // The broken module shows an error banner and its raw source
+    let broken = fs::read_to_string(base.join("broken_module/broken/index.html"))?;
+    assert!(broken.contains("error-banner"));
+    assert!(base.join("src/broken_module/broken.html").exists());
+
+    // Other modules are documented as usual
+    let valid = fs::read_to_string(base.join("broken_module/valid/const.VALUE.html"))?;
+    assert!(valid.contains("sibling module is broken"));
+
+    Ok(())
+}
+
+#[test]
+fn broken_module_fails_in_strict_mode() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+
+    let result = Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/broken_module",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+        "--strict",
+    ])
+    .run();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn broken_module_writes_coverage_in_strict_mode() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let json_path = tmp_dir.path().join("coverage.json");
+
+    let result = Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/broken_module",
+        "--output",
+        tmp_dir.path().join("docs").to_str().unwrap(),
+        "--strict",
+        "--coverage-json",
+        json_path.to_str().unwrap(),
+    ])
+    .run();
+
+    // The report is written before the run fails
+    assert!(result.is_err());
+    let json = fs::read_to_string(&json_path).unwrap();
+    assert!(json.contains("\"package\": \"broken_module\""));
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use std::{fs, path::Path, time::SystemTime};
+use wesldoc::{Args, Parser};
+
+fn run(path: &Path, output: &Path) -> Result<()> {
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        path.to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .run()
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn modified(path: &Path) -> Result<SystemTime> {
+    Ok(fs::metadata(path)?.modified()?)
+}
+
+#[test]
+fn unchanged_packages_and_modules_are_reused() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let sources = tmp_dir.path().join("sources");
+    copy_dir(Path::new("fixtures/standalone"), &sources)?;
+    let output = tmp_dir.path().join("output");
+    run(&sources.join("app"), &output)?;
+
+    let geometry_cache = output.join("geometry/1.0.0/.wesldoc-cache.json");
+    let app_index = output.join("app/0.2.0/app/index.html");
+    let geometry_modified = modified(&geometry_cache)?;
+    let app_index_modified = modified(&app_index)?;
+
+    // Change a function body, only the pages of its module are rendered again
+    let scene = sources.join("app/shaders/scene.wesl");
+    fs::write(
+        &scene,
+        fs::read_to_string(&scene)?.replace("Point(0.0, 0.0)", "Point(1.0, 0.0)"),
+    )?;
+    run(&sources.join("app"), &output)?;
+
+    // The unchanged dependency is skipped
+    assert_eq!(modified(&geometry_cache)?, geometry_modified);
+    assert_eq!(modified(&app_index)?, app_index_modified);
+    let source = fs::read_to_string(output.join("app/0.2.0/src/app/scene.html"))?;
+    assert!(source.contains("1.0, 0.0"));
+
+    // Temporary directories are moved into place
+    for package in ["app", "geometry"] {
+        for entry in fs::read_dir(output.join(package))? {
+            let name = entry?.file_name();
+            assert!(
+                !name.to_string_lossy().starts_with('.'),
+                "{name:?} left over"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn changed_dependencies_are_documented_again() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let sources = tmp_dir.path().join("sources");
+    copy_dir(Path::new("fixtures/standalone"), &sources)?;
+    let output = tmp_dir.path().join("output");
+    run(&sources.join("app"), &output)?;
+
+    let point = sources.join("geometry/shaders/point.wesl");
+    fs::write(
+        &point,
+        fs::read_to_string(&point)?.replace("A point in the plane.", "A point in 2D."),
+    )?;
+    run(&sources.join("app"), &output)?;
+
+    let page = fs::read_to_string(output.join("geometry/1.0.0/geometry/point/struct.Point.html"))?;
+    assert!(page.contains("A point in 2D."));
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use std::{fs, path::Path, process::Command};
+use wesldoc::{Args, Parser};
+
+fn run(args: &[&str], output: &Path) -> Result<()> {
+    Args::parse_from(
+        ["wesldoc", "doc", "--output", output.to_str().unwrap()]
+            .into_iter()
+            .chain(args.iter().copied()),
+    )
+    .run()
+}
+
+#[test]
+fn manifest_path_offline() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(
+        &[
+            "--manifest-path",
+            "../example_packages/pbr/Cargo.toml",
+            "--offline",
+        ],
+        tmp_dir.path(),
+    )?;
+
+    for package in ["pbr", "math_utils", "primitives"] {
+        assert!(tmp_dir.path().join(package).is_dir());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pre_generated_metadata_json() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    // Dump the metadata once, wesldoc does not run cargo afterwards
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let metadata = Command::new(cargo)
+        .args([
+            "metadata",
+            "--format-version",
+            "1",
+            "--offline",
+            "--manifest-path",
+            "../example_packages/pbr/Cargo.toml",
+        ])
+        .output()?;
+    assert!(metadata.status.success());
+    let metadata_json = tmp_dir.path().join("metadata.json");
+    fs::write(&metadata_json, metadata.stdout)?;
+
+    let output = tmp_dir.path().join("docs");
+    run(
+        &["--metadata-json", metadata_json.to_str().unwrap()],
+        &output,
+    )?;
+
+    for package in ["pbr", "math_utils", "primitives"] {
+        assert!(output.join(package).is_dir());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invalid_metadata_json() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let metadata_json = tmp_dir.path().join("metadata.json");
+    fs::write(&metadata_json, "{}")?;
+
+    let err = run(
+        &["--metadata-json", metadata_json.to_str().unwrap()],
+        tmp_dir.path(),
+    )
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("invalid cargo metadata"));
+
+    Ok(())
+}
+
+#[test]
+fn metadata_json_conflicts_with_manifest_path() {
+    assert!(
+        Args::try_parse_from([
+            "wesldoc",
+            "doc",
+            "--metadata-json",
+            "metadata.json",
+            "--manifest-path",
+            "Cargo.toml",
+        ])
+        .is_err()
+    );
+}
@@ -0,0 +1,25 @@
+use wesldoc::{Args, Parser, exit_code};
+
+fn check(args: &[&str]) -> anyhow::Result<()> {
+    Args::parse_from(["wesldoc", "check"].into_iter().chain(args.iter().copied())).run()
+}
+
+#[test]
+fn check_passes() {
+    assert!(check(&["fixtures/lint_levels", "--no-deps"]).is_ok());
+}
+
+#[test]
+fn check_fails_on_lint_errors() {
+    let err = check(&["fixtures/lint_levels", "--missing-docs", "deny"]).unwrap_err();
+    assert_eq!(exit_code(&err), 1);
+
+    let err = check(&["fixtures/broken_module", "--strict"]).unwrap_err();
+    assert_eq!(exit_code(&err), 1);
+}
+
+#[test]
+fn check_fails_without_package() {
+    let err = check(&["fixtures/does_not_exist"]).unwrap_err();
+    assert_eq!(exit_code(&err), 2);
+}
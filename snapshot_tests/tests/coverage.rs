@@ -0,0 +1,75 @@
+use anyhow::Result;
+use std::fs;
+use wesldoc::{Args, Parser};
+
+fn run(output: &std::path::Path, extra_args: &[&str]) -> Result<()> {
+    Args::parse_from(
+        [
+            "wesldoc",
+            "doc",
+            "fixtures/lint_levels",
+            "--output",
+            output.to_str().unwrap(),
+        ]
+        .into_iter()
+        .chain(extra_args.iter().copied()),
+    )
+    .run()
+}
+
+#[test]
+fn coverage_report() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let json_path = tmp_dir.path().join("coverage.json");
+
+    run(
+        &tmp_dir.path().join("docs"),
+        &[
+            "--statistics",
+            "--coverage-json",
+            json_path.to_str().unwrap(),
+        ],
+    )?;
+
+    let json = fs::read_to_string(&json_path)?;
+    assert!(json.contains("\"package\": \"lint_levels\""));
+    assert!(json.contains("\"module\": \"lint_levels::api::inner\""));
+    assert!(json.contains("\"function_parameter\""));
+
+    // The package index shows a coverage badge
+    let index = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("docs/lint_levels/latest/lint_levels/index.html"),
+    )?;
+    assert!(index.contains("coverage-badge"));
+
+    Ok(())
+}
+
+#[test]
+fn min_coverage() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+
+    // 5 of 9 elements are documented, `helpers` and its function, `helper` and its parameter are not
+    assert!(run(tmp_dir.path(), &["--min-coverage", "50"]).is_ok());
+    assert!(run(tmp_dir.path(), &["--min-coverage", "100"]).is_err());
+
+    // The report is written before the threshold fails the run
+    let json_path = tmp_dir.path().join("coverage.json");
+    let result = run(
+        tmp_dir.path(),
+        &[
+            "--min-coverage",
+            "100",
+            "--coverage-json",
+            json_path.to_str().unwrap(),
+        ],
+    );
+    assert!(result.is_err());
+    assert!(
+        fs::read_to_string(&json_path)
+            .unwrap()
+            .contains("\"package\": \"lint_levels\"")
+    );
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+fn diff(old: &str, new: &str, format: &str, output: &Path) -> Result<String> {
+    Args::parse_from([
+        "wesldoc",
+        "diff",
+        old,
+        new,
+        "--format",
+        format,
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .run()?;
+    Ok(fs::read_to_string(output)?)
+}
+
+#[test]
+fn diff_source_trees() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let report = diff(
+        "fixtures/diff/old",
+        "fixtures/diff/new",
+        "text",
+        &tmp_dir.path().join("report.txt"),
+    )?;
+
+    assert!(report.starts_with("Changes in lights from 1.0.0 to 1.1.0"));
+    assert!(report.contains("removed      fn lighting::attenuation"));
+    assert!(report.contains("added        fn lighting::falloff"));
+    assert!(report.contains("signature    fn lighting::shade"));
+    assert!(report.contains("value        const lighting::MAX_LIGHTS"));
+    assert!(report.contains("layout       struct lighting::Light: members reordered"));
+    assert!(report.contains("binding      var lighting::light"));
+    assert!(report.contains("conditional  fn lighting::shadow"));
+
+    Ok(())
+}
+
+#[test]
+fn diff_json() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let report = diff(
+        "fixtures/diff/old",
+        "fixtures/diff/new",
+        "json",
+        &tmp_dir.path().join("report.json"),
+    )?;
+
+    assert!(report.contains(r#""old_version": "1.0.0""#));
+    assert!(report.contains(r#""new_version": "1.1.0""#));
+    assert!(report.contains(r#""kind": "binding""#));
+    assert!(report.contains(r#""old": "@group(0) @binding(0)""#));
+    assert!(report.contains(r#""new": "@group(0) @binding(1)""#));
+
+    Ok(())
+}
+
+#[test]
+fn diff_unchanged() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let report = diff(
+        "fixtures/diff/old",
+        "fixtures/diff/old",
+        "text",
+        &tmp_dir.path().join("report.txt"),
+    )?;
+    assert!(report.contains("no API changes"));
+
+    Ok(())
+}
+
+#[test]
+fn diff_renamed_parameters() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let report = diff(
+        "fixtures/diff/old",
+        "fixtures/diff/renamed",
+        "text",
+        &tmp_dir.path().join("report.txt"),
+    )?;
+    assert!(report.contains("no API changes"));
+
+    Ok(())
+}
+
+#[test]
+fn changes_page() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    for version in ["old", "new"] {
+        Args::parse_from([
+            "wesldoc",
+            "doc",
+            &format!("fixtures/diff/{version}"),
+            "--output",
+            tmp_dir.path().to_str().unwrap(),
+        ])
+        .run()?;
+    }
+    let lights = tmp_dir.path().join("lights");
+
+    // The changes page is linked from the version selector through common.js
+    let common = fs::read_to_string(lights.join("common.js"))?;
+    assert!(common.contains(r#""1.1.0": "1.0.0""#));
+    let changes = fs::read_to_string(lights.join("1.1.0/lights/changes.html"))?;
+    assert!(changes.contains("Changes since 1.0.0"));
+    assert!(changes.contains("fn lighting::falloff"));
+    assert!(lights.join("latest/lights/changes.html").is_file());
+    assert!(!lights.join("1.0.0/lights/changes.html").exists());
+
+    // Documented versions can be compared directly
+    let report = diff(
+        lights.join("1.0.0").to_str().unwrap(),
+        lights.join("1.1.0").to_str().unwrap(),
+        "html",
+        &tmp_dir.path().join("report.html"),
+    )?;
+    assert!(report.contains("Changes in lights from 1.0.0 to 1.1.0"));
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+fn run(path: &str, output: &Path, extra_args: &[&str]) -> Result<()> {
+    Args::parse_from(
+        ["wesldoc", "doc", path, "--output", output.to_str().unwrap()]
+            .into_iter()
+            .chain(extra_args.iter().copied()),
+    )
+    .run()
+}
+
+#[test]
+fn extern_html_root_url_from_metadata() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run("fixtures/extern_docs", tmp_dir.path(), &[])?;
+
+    // Hosted dependencies are not documented locally
+    assert!(tmp_dir.path().join("extern_docs").is_dir());
+    assert!(!tmp_dir.path().join("primitives").exists());
+
+    let volume = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("extern_docs/latest/extern_docs/volume/fn.volume.html"),
+    )?;
+    assert!(volume.contains(
+        "https://docs.example.com/primitives/0.0.0/primitives/cuboid/struct.Cuboid.html"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn extern_html_root_url_overrides_metadata() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(
+        "fixtures/extern_docs",
+        tmp_dir.path(),
+        &[
+            "--extern-html-root-url",
+            "primitives=https://mirror.example.com/primitives",
+        ],
+    )?;
+
+    let volume = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("extern_docs/latest/extern_docs/volume/fn.volume.html"),
+    )?;
+    assert!(
+        volume
+            .contains("https://mirror.example.com/primitives/primitives/cuboid/struct.Cuboid.html")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn extern_html_root_url_for_transitive_dependency() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(
+        "../example_packages/pbr",
+        tmp_dir.path(),
+        &[
+            "--extern-html-root-url",
+            "math_utils=https://docs.example.com/math_utils/0.0.0",
+        ],
+    )?;
+
+    assert!(tmp_dir.path().join("pbr").is_dir());
+    assert!(!tmp_dir.path().join("math_utils").exists());
+
+    Ok(())
+}
+
+#[test]
+fn invalid_extern_html_root_url() {
+    assert!(
+        Args::try_parse_from([
+            "wesldoc",
+            "doc",
+            "fixtures/extern_docs",
+            "--extern-html-root-url",
+            "primitives",
+        ])
+        .is_err()
+    );
+}
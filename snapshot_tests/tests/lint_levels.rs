@@ -0,0 +1,47 @@
+use anyhow::Result;
+use std::fs;
+use wesldoc::{Args, Parser};
+
+fn run(output: &std::path::Path, extra_args: &[&str]) -> Result<()> {
+    Args::parse_from(
+        [
+            "wesldoc",
+            "doc",
+            "fixtures/lint_levels",
+            "--output",
+            output.to_str().unwrap(),
+        ]
+        .into_iter()
+        .chain(extra_args.iter().copied()),
+    )
+    .run()
+}
+
+#[test]
+fn in_source_lint_levels() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    // `api` denies missing docs but allows them on `helper`, `helpers` only warns
+    run(tmp_dir.path(), &[])?;
+
+    // Lint attributes and lines are not rendered
+    let base = tmp_dir.path().join("lint_levels/latest/lint_levels");
+    let helper = fs::read_to_string(base.join("api/fn.helper.html"))?;
+    assert!(!helper.contains("wesldoc"));
+    let api = fs::read_to_string(base.join("api/index.html"))?;
+    assert!(!api.contains("@lint"));
+
+    Ok(())
+}
+
+#[test]
+fn in_source_lint_levels_parse_only() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(tmp_dir.path(), &["--parse-only"])
+}
+
+#[test]
+fn cli_lint_level_overrides_wesl_toml() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    assert!(run(tmp_dir.path(), &["--missing-docs", "deny"]).is_err());
+}
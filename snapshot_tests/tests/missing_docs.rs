@@ -0,0 +1,26 @@
+use wesldoc::{Args, Parser};
+
+fn run(missing_docs: &str) -> anyhow::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/name_collisions",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+        "--missing-docs",
+        missing_docs,
+    ])
+    .run()
+}
+
+#[test]
+fn missing_docs_warn_still_generates() {
+    assert!(run("warn").is_ok());
+}
+
+#[test]
+fn missing_docs_deny_fails() {
+    assert!(run("deny").is_err());
+}
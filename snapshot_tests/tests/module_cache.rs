@@ -0,0 +1,36 @@
+use anyhow::Result;
+use snapshot_tests::{DEPENDENCY_MODULES, synthetic_package};
+use std::path::Path;
+use wesldoc::{Args, Parser};
+
+// The parse counter is shared by the whole process, so this binary has a single test.
+
+#[test]
+fn modules_are_parsed_once_per_run() -> Result<()> {
+    const MODULES: usize = 20;
+    let tmp_dir = tempfile::tempdir()?;
+    synthetic_package(tmp_dir.path(), MODULES);
+    let app = tmp_dir.path().join("app");
+    // The shared module, the modules importing it and the modules of the dependency
+    let files = 1 + MODULES + DEPENDENCY_MODULES;
+
+    // Re-exports and const assertions are documented from the syntax trees of the cache too
+    assert_eq!(parsed_modules(&app, &[])?, files);
+    assert_eq!(parsed_modules(&app, &["--parse-only"])?, files);
+    // Without the cache, imported modules are parsed again by every importing module
+    assert!(parsed_modules(&app, &["--no-module-cache"])? > files);
+
+    Ok(())
+}
+
+/// The number of modules parsed while checking `path`.
+fn parsed_modules(path: &Path, extra_args: &[&str]) -> Result<usize> {
+    let before = wesldoc::parsed_modules();
+    Args::parse_from(
+        ["wesldoc", "check", path.to_str().unwrap()]
+            .into_iter()
+            .chain(extra_args.iter().copied()),
+    )
+    .run()?;
+    Ok(wesldoc::parsed_modules() - before)
+}
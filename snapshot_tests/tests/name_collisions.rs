@@ -0,0 +1,58 @@
+use anyhow::Result;
+use std::fs;
+use wesldoc::{Args, Parser};
+
+#[test]
+fn same_name_in_different_modules() -> Result<()> {
+    check_links(&[])
+}
+
+#[test]
+fn same_name_in_different_modules_parse_only() -> Result<()> {
+    check_links(&["--parse-only"])
+}
+
+#[test]
+fn same_name_in_different_modules_hash_mangler() -> Result<()> {
+    check_links(&["--mangler", "hash"])
+}
+
+fn check_links(extra_args: &[&str]) -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    Args::parse_from(
+        [
+            "wesldoc",
+            "doc",
+            "fixtures/name_collisions",
+            "--output",
+            tmp_dir.path().to_str().unwrap(),
+        ]
+        .into_iter()
+        .chain(extra_args.iter().copied()),
+    )
+    .run()?;
+
+    let page = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("name_collisions/latest/name_collisions/user/fn.combine.html"),
+    )?;
+
+    // Parameter types and intra-doc links must point to the module the item was imported from
+    for module in ["a", "b"] {
+        let href = format!(r#"href="../{module}/constants/struct.Shared.html""#);
+        assert_eq!(page.matches(&href).count(), 2, "{href}");
+    }
+
+    // Names that are not imported are not linked to an item of the same name elsewhere
+    let page = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("name_collisions/latest/name_collisions/other/fn.unrelated.html"),
+    )?;
+    assert!(page.contains("Unrelated to"));
+    assert!(!page.contains("struct.Shared.html"));
+
+    Ok(())
+}
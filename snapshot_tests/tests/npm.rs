@@ -0,0 +1,61 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+fn run(path: &Path, output: &Path) -> Result<()> {
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        path.to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .run()
+}
+
+#[test]
+fn npm_dependencies_from_node_modules() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(Path::new("fixtures/npm_project"), tmp_dir.path())?;
+
+    // Versions come from the installed packages, including the nested `colors` of `@wesl/noise`
+    assert!(tmp_dir.path().join("npm_project/0.1.0").is_dir());
+    assert!(tmp_dir.path().join("wesl_noise/1.2.0").is_dir());
+    assert!(tmp_dir.path().join("colors/2.1.0").is_dir());
+    assert!(tmp_dir.path().join("colors/1.0.0").is_dir());
+
+    // Packages without wesl files are skipped
+    assert!(!tmp_dir.path().join("left_pad").exists());
+
+    // Hoisted and nested dependencies are linked to the installed versions
+    let gray = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("npm_project/0.1.0/npm_project/main/fn.gray.html"),
+    )?;
+    assert!(gray.contains("colors/2.1.0/colors/srgb/struct.Srgb.html"));
+    assert!(gray.contains("wesl_noise/1.2.0/wesl_noise/value/struct.NoiseParams.html"));
+    let params = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("wesl_noise/1.2.0/wesl_noise/value/struct.NoiseParams.html"),
+    )?;
+    assert!(params.contains("colors/1.0.0/colors/gamma/struct.Gamma.html"));
+
+    Ok(())
+}
+
+#[test]
+fn npm_dependency_not_installed() -> Result<()> {
+    let project = tempfile::tempdir()?;
+    fs::write(
+        project.path().join("package.json"),
+        r#"{ "name": "app", "version": "1.0.0", "dependencies": { "missing": "^1.0.0" } }"#,
+    )?;
+
+    let tmp_dir = tempfile::tempdir()?;
+    let err = run(project.path(), tmp_dir.path()).unwrap_err();
+    assert!(format!("{err:#}").contains("not installed"));
+
+    Ok(())
+}
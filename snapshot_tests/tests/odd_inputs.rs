@@ -0,0 +1,171 @@
+use anyhow::Result;
+use proptest::prelude::*;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+// Odd but valid inputs must never panic and must not fail any module.
+
+#[test]
+fn odd_inputs() -> Result<()> {
+    document(&[])
+}
+
+#[test]
+fn odd_inputs_parse_only() -> Result<()> {
+    document(&["--parse-only"])
+}
+
+fn document(extra_args: &[&str]) -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    Args::parse_from(
+        [
+            "wesldoc",
+            "doc",
+            "fixtures/odd_inputs",
+            "--output",
+            tmp_dir.path().to_str().unwrap(),
+            "--strict",
+        ]
+        .into_iter()
+        .chain(extra_args.iter().copied()),
+    )
+    .run()
+}
+
+// Generated inputs may be invalid, so they may fail to compile, but must never panic.
+
+const DIRECTIVES: &[&str] = &[
+    "enable f16;",
+    "requires readonly_and_readwrite_storage_textures;",
+    "diagnostic(off, derivative_uniformity);",
+    "@if(feature_a) enable f16;",
+];
+
+const CONDITIONS: &[&str] = &[
+    "",
+    "@if(feature_a)",
+    "@if(!feature_a && (feature_b || feature_c))",
+    "@elif(feature_b)",
+    "@else",
+];
+
+const DOCS: &[&str] = &[
+    "",
+    "/// Plain.",
+    "/// Links to [`Item0_0`], [`package::m0::Item0_1`] and [missing].",
+    "/// # Heading\n/// * [`super::Item0_0`]\n/// ```wgsl\n/// let x = 1;\n/// ```",
+    "/** Block comment with [`Item1_0`]. */",
+];
+
+const TYPES: &[&str] = &[
+    "f32",
+    "vec3<f32>",
+    "array<u32, 4>",
+    "array<atomic<u32>>",
+    "atomic<i32>",
+    "mat4x4<f32>",
+    "texture_2d<f32>",
+    "sampler",
+    "ptr<storage, atomic<u32>, read_write>",
+    "Item0_0",
+    "package::m0::Item0_1",
+];
+
+/// A declaration as `(kind, type, doc comment, condition)` indices.
+type Decl = (usize, usize, usize, usize);
+
+/// A module as `(directives, imports as (module, item, publish), declarations)`.
+type Module = (Vec<usize>, Vec<(usize, usize, bool)>, Vec<Decl>);
+
+fn module() -> impl Strategy<Value = Module> {
+    (
+        prop::collection::vec(0..DIRECTIVES.len(), 0..3),
+        prop::collection::vec((0..3usize, 0..4usize, any::<bool>()), 0..3),
+        prop::collection::vec(
+            (
+                0..9usize,
+                0..TYPES.len(),
+                0..DOCS.len(),
+                0..CONDITIONS.len(),
+            ),
+            0..6,
+        ),
+    )
+}
+
+fn render_decl(name: &str, idx: usize, (kind, ty, doc, condition): Decl) -> String {
+    let ty = TYPES[ty];
+    let decl = match kind {
+        0 => format!("const {name}: {ty} = {ty}();"),
+        1 => format!("@id({idx}) override {name}: f32 = 1.0;"),
+        2 => format!("var<private> {name}: {ty};"),
+        3 => format!("@group(0) @binding({idx}) var<storage, read_write> {name}: {ty};"),
+        4 => {
+            format!("struct {name} {{\n    /// A member.\n    @align(16) a: {ty},\n    b: f32\n}}")
+        }
+        5 => format!(
+            "fn {name}(p: {ty}, @builtin(local_invocation_index) i: u32) -> {ty} {{ return p; }}"
+        ),
+        6 => format!("alias {name} = {ty};"),
+        7 => format!("@compute @workgroup_size(8, 8) fn {name}() {{}}"),
+        _ => "const_assert 1 == 1;".to_string(),
+    };
+    format!("{}\n{}\n{decl}\n", DOCS[doc], CONDITIONS[condition])
+}
+
+fn write_package(dir: &Path, modules: &[Module]) -> Result<()> {
+    fs::create_dir_all(dir.join("shaders"))?;
+    fs::write(
+        dir.join("wesl.toml"),
+        "[package]\nname = \"generated\"\nversion = \"0.1.0\"\nedition = \"unstable_2025\"\nroot = \"shaders\"\n",
+    )?;
+    for (m, (directives, imports, decls)) in modules.iter().enumerate() {
+        let mut source = String::from("//! A generated module.\n\n");
+        for directive in directives {
+            source.push_str(DIRECTIVES[*directive]);
+            source.push('\n');
+        }
+        for (module, item, publish) in imports {
+            if *publish {
+                source.push_str("@publish ");
+            }
+            source.push_str(&format!(
+                "import package::m{module}::Item{module}_{item};\n"
+            ));
+        }
+        for (idx, decl) in decls.iter().enumerate() {
+            source.push_str(&render_decl(&format!("Item{m}_{idx}"), idx, *decl));
+        }
+        fs::write(dir.join(format!("shaders/m{m}.wesl")), source)?;
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn generated_inputs_never_panic(modules in prop::collection::vec(module(), 1..4)) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let package = tmp_dir.path().join("generated");
+        write_package(&package, &modules).unwrap();
+
+        for extra_args in [&[][..], &["--parse-only"]] {
+            let output = tempfile::tempdir().unwrap();
+            let args = Args::parse_from(
+                [
+                    "wesldoc",
+                    "doc",
+                    package.to_str().unwrap(),
+                    "--output",
+                    output.path().to_str().unwrap(),
+                ]
+                .into_iter()
+                .chain(extra_args.iter().copied()),
+            );
+            // Errors are expected for invalid inputs
+            let _ = args.run();
+        }
+    }
+}
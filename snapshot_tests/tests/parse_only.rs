@@ -0,0 +1,82 @@
+use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use wesldoc::{Args, Parser};
+
+fn document(path: &str, output: &Path, extra_args: &[&str]) -> Result<()> {
+    Args::parse_from(
+        ["wesldoc", "doc", path, "--output", output.to_str().unwrap()]
+            .into_iter()
+            .chain(extra_args.iter().copied()),
+    )
+    .run()
+}
+
+/// The generated pages by their relative path, each with the links it contains.
+fn pages(output: &Path) -> Result<BTreeMap<PathBuf, Vec<String>>> {
+    let mut pages = BTreeMap::new();
+    let mut dirs = vec![output.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "html") {
+                let page = fs::read_to_string(&path)?;
+                let links = page
+                    .split("href=\"")
+                    .skip(1)
+                    .filter_map(|rest| rest.split('"').next())
+                    .map(str::to_string)
+                    .collect();
+                pages.insert(path.strip_prefix(output)?.to_path_buf(), links);
+            }
+        }
+    }
+    Ok(pages)
+}
+
+/// Parsing modules directly must document the same items with the same links as compiling them.
+fn check_same_output(path: &str) -> Result<()> {
+    let compiled = tempfile::tempdir()?;
+    document(path, compiled.path(), &[])?;
+    let parsed = tempfile::tempdir()?;
+    document(path, parsed.path(), &["--parse-only"])?;
+
+    let compiled = pages(compiled.path())?;
+    let parsed = pages(parsed.path())?;
+    assert!(!compiled.is_empty());
+    assert_eq!(
+        compiled.keys().collect::<Vec<_>>(),
+        parsed.keys().collect::<Vec<_>>(),
+        "different pages for {path}"
+    );
+    for (page, links) in &compiled {
+        assert_eq!(
+            links,
+            &parsed[page],
+            "different links in {}",
+            page.display()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parse_only_matches_compiled_name_collisions() -> Result<()> {
+    check_same_output("fixtures/name_collisions")
+}
+
+#[test]
+fn parse_only_matches_compiled_dependencies() -> Result<()> {
+    check_same_output("fixtures/standalone/app")
+}
+
+#[test]
+fn parse_only_matches_compiled_re_exports() -> Result<()> {
+    check_same_output("fixtures/re_exports/app")
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use std::fs;
+use wesldoc::{Args, Parser};
+
+#[test]
+fn path_dependency_versions() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/path_versions",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+    ])
+    .run()?;
+
+    let page = tmp_dir
+        .path()
+        .join("path_versions/latest/path_versions/app/fn.combine.html");
+    let combine = fs::read_to_string(&page)?;
+
+    // Versions from wesl.toml and package.json
+    assert!(combine.contains("tagged/3.1.0/tagged/a/struct.A.html"));
+    assert!(combine.contains("scripted/2.0.0/scripted/b/struct.B.html"));
+
+    // Version inherited from the cargo workspace
+    assert!(combine.contains("inherited/4.2.0/inherited/d/struct.D.html"));
+
+    // Content-hash pseudo-version without any version
+    assert!(combine.contains("unversioned/0.0.0-local-"));
+    assert!(!combine.contains("/0.0.0/"));
+
+    // Path dependencies are documented, so the links resolve
+    let links = combine
+        .split("href=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .filter(|href| href.contains("/struct."))
+        .collect::<Vec<_>>();
+    for link in links {
+        let target = page.parent().unwrap().join(link.split('#').next().unwrap());
+        assert!(target.is_file(), "broken link {link}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+/// The part of an overview page listing the re-exports.
+fn re_exports(page: &str) -> &str {
+    let start = page.find(">Re-exports</h3>").expect("no re-exports");
+    let end = page[start..].find("</ul>").unwrap();
+    &page[start..start + end]
+}
+
+/// The search index entry of `name` in the module at `path`.
+fn search_entry<'a>(items: &'a str, path: &[&str], name: &str) -> &'a str {
+    let path = path
+        .iter()
+        .map(|c| format!("\"{c}\""))
+        .collect::<Vec<_>>()
+        .join(",");
+    let start = items
+        .find(&format!("\"path\":[{path}],\"name\":\"{name}\""))
+        .unwrap_or_else(|| panic!("{name} not in search index"));
+    let end = items[start..].find('}').unwrap();
+    &items[start..start + end]
+}
+
+fn assert_link_exists(page: &Path, html: &str, href: &str) {
+    assert!(
+        html.contains(&format!("href=\"{href}\"")),
+        "missing link {href}"
+    );
+    assert!(
+        page.parent().unwrap().join(href).is_file(),
+        "broken link {href}"
+    );
+}
+
+#[test]
+fn re_exports_link_to_canonical_items() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/re_exports/app",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+    ])
+    .run()?;
+    let docs = tmp_dir.path().join("app/latest");
+
+    // Items of the package and of dependencies, with the conditional of the defining module
+    let prelude_page = docs.join("app/prelude/index.html");
+    let prelude = fs::read_to_string(&prelude_page)?;
+    let section = re_exports(&prelude);
+    assert_link_exists(&prelude_page, section, "../shapes/struct.Circle.html");
+    assert_link_exists(&prelude_page, section, "../shapes/const.UNIT.html");
+    assert_link_exists(
+        &prelude_page,
+        section,
+        "../../../../geometry/1.0.0/geometry/point/struct.Point.html",
+    );
+    assert!(section.contains("package::shapes::Circle"));
+    assert!(section.contains("geometry::point::Point"));
+    assert!(section.contains(r#"<span class="conditional-inline">circles</span>"#));
+
+    // Re-exports of re-exports link to the defining module
+    let api_page = docs.join("app/api/index.html");
+    let api = fs::read_to_string(&api_page)?;
+    let section = re_exports(&api);
+    assert_link_exists(&api_page, section, "../shapes/struct.Circle.html");
+    assert!(section.contains("package::shapes::Circle"));
+    assert!(section.contains(r#"<span class="conditional-inline">circles</span>"#));
+
+    // Re-exported items are found by search under the re-exporting module
+    let items = fs::read_to_string(docs.join("items.js"))?;
+    let circle = search_entry(&items, &["app", "prelude"], "Circle");
+    assert!(circle.contains("\"kind\":\"Struct\""));
+    assert!(circle.contains("A circle."));
+    assert!(circle.contains("\"url\":\"app/shapes/struct.Circle.html\""));
+    let circle = search_entry(&items, &["app", "api"], "Circle");
+    assert!(circle.contains("\"url\":\"app/shapes/struct.Circle.html\""));
+    let unit = search_entry(&items, &["app", "prelude"], "UNIT");
+    assert!(unit.contains("\"kind\":\"Constant\""));
+    let point = search_entry(&items, &["app", "prelude"], "Point");
+    assert!(point.contains("\"url\":\"../../geometry/1.0.0/geometry/point/struct.Point.html\""));
+    assert!(
+        docs.join("../../geometry/1.0.0/geometry/point/struct.Point.html")
+            .is_file()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+fn document(output: &Path) -> Result<()> {
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/requirements",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .run()
+}
+
+/// The part of a page from the section title up to the next section.
+fn section<'a>(page: &'a str, title: &str) -> &'a str {
+    let start = page
+        .find(&format!(">{title}</h3>"))
+        .unwrap_or_else(|| panic!("section {title} not found"));
+    let rest = &page[start..];
+    let end = rest[1..].find("<h3").map_or(rest.len(), |end| end + 1);
+    &rest[..end]
+}
+
+#[test]
+fn requirements_and_static_assertions() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    document(tmp_dir.path())?;
+    let docs = tmp_dir.path().join("requirements/latest/requirements");
+
+    // Directives are listed with their conditionals
+    let gpu = fs::read_to_string(docs.join("gpu/index.html"))?;
+    let requirements = section(&gpu, "Requirements");
+    assert!(requirements.contains("enable f16;"));
+    assert!(requirements.contains("requires readonly_and_readwrite_storage_textures;"));
+    assert!(requirements.contains("diagnostic(off, derivative_uniformity);"));
+    assert!(requirements.contains("enable subgroups;"));
+    assert!(requirements.contains("subgroups"));
+
+    // Only the module's own assertions are listed, not the ones of imported modules
+    let assertions = section(&gpu, "Static assertions");
+    assert!(assertions.contains("const_assert TILE_LIGHTS == 4u;"));
+    assert!(assertions.contains("Tiles hold at least one light."));
+    assert!(assertions.contains("tiled"));
+    assert!(!assertions.contains("MAX_LIGHTS == 8u"));
+    assert!(assertions.contains("src/requirements/gpu.html#"));
+    let limits = fs::read_to_string(docs.join("limits/index.html"))?;
+    assert!(section(&limits, "Static assertions").contains("const_assert MAX_LIGHTS == 8u;"));
+
+    // Imported directives and assertions are not listed, even if they are at the same position in
+    // their module as one of the module's own
+    let shading = fs::read_to_string(docs.join("shading/index.html"))?;
+    assert!(!shading.contains(">Requirements</h3>"));
+    let assertions = section(&shading, "Static assertions");
+    assert_eq!(assertions.matches("const_assert").count(), 1);
+    assert!(assertions.contains("const_assert BIAS != 0.0001;"));
+    let precision = fs::read_to_string(docs.join("precision/index.html"))?;
+    assert!(section(&precision, "Requirements").contains("enable f16;"));
+    assert!(section(&precision, "Static assertions").contains("const_assert EPSILON != 0.0;"));
+
+    // The root lists the extensions required by all modules
+    let root = fs::read_to_string(docs.join("index.html"))?;
+    let extensions = section(&root, "Required extensions");
+    assert!(extensions.contains("<code>f16</code>"));
+    assert!(extensions.contains("<code>readonly_and_readwrite_storage_textures</code>"));
+    assert!(extensions.contains("<code>subgroups</code>"));
+    assert!(!gpu.contains(">Required extensions</h3>"));
+
+    Ok(())
+}
+
+#[test]
+fn parse_only_lists_own_static_assertions() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/requirements",
+        "--parse-only",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+    ])
+    .run()?;
+    let docs = tmp_dir.path().join("requirements/latest/requirements");
+
+    let gpu = fs::read_to_string(docs.join("gpu/index.html"))?;
+    let assertions = section(&gpu, "Static assertions");
+    assert!(assertions.contains("const_assert TILE_LIGHTS == 4u;"));
+    assert!(!assertions.contains("MAX_LIGHTS == 8u"));
+    let shading = fs::read_to_string(docs.join("shading/index.html"))?;
+    assert!(!shading.contains(">Requirements</h3>"));
+    let assertions = section(&shading, "Static assertions");
+    assert_eq!(assertions.matches("const_assert").count(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use wesldoc::{Args, Parser, exit_code};
+
+fn semver_check(old: &str, new: &str) -> anyhow::Result<()> {
+    Args::parse_from(["wesldoc", "semver-check", old, new]).run()
+}
+
+#[test]
+fn breaking_changes_require_major_bump() {
+    let err = semver_check("fixtures/diff/old", "fixtures/diff/new").unwrap_err();
+    assert_eq!(exit_code(&err), 1);
+    assert!(err.to_string().contains("no major version bump"));
+
+    assert!(semver_check("fixtures/diff/old", "fixtures/diff/major").is_ok());
+}
+
+#[test]
+fn unchanged_api_passes() {
+    assert!(semver_check("fixtures/diff/old", "fixtures/diff/old").is_ok());
+}
+
+#[test]
+fn renamed_parameters_are_not_breaking() {
+    // Only the parameter names of `shade` differ, which callers can't observe
+    assert!(semver_check("fixtures/diff/old", "fixtures/diff/renamed").is_ok());
+}
+
+#[test]
+fn breaking_changes_are_marked() -> anyhow::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let output = tmp_dir.path().join("report.txt");
+    Args::parse_from([
+        "wesldoc",
+        "diff",
+        "fixtures/diff/old",
+        "fixtures/diff/new",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .run()?;
+    let report = fs::read_to_string(output)?;
+
+    let line = |item: &str| {
+        report
+            .lines()
+            .find(|line| line.contains(item))
+            .unwrap()
+            .to_string()
+    };
+    assert!(line("fn lighting::attenuation").ends_with("[breaking]"));
+    assert!(line("members reordered").ends_with("[breaking]"));
+    assert!(line("var lighting::light").ends_with("[breaking]"));
+    // Declaring an item under narrower conditions removes it for some consumers
+    assert!(line("fn lighting::shadow").ends_with("[breaking]"));
+    assert!(!line("fn lighting::falloff").ends_with("[breaking]"));
+    assert!(!line("const lighting::MAX_LIGHTS").ends_with("[breaking]"));
+    // The new feature is added, no feature is removed
+    assert!(line("feature soft_shadows").contains("added"));
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+use wesldoc::{Args, DocServer, Parser};
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn get(address: &str, path: &str) -> Option<String> {
+    let mut stream = TcpStream::connect(address).ok()?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n"
+    )
+    .ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    Some(response)
+}
+
+/// Polls `path` until the response satisfies `f`.
+fn wait_for(address: &str, path: &str, f: impl Fn(&str) -> bool) -> String {
+    let started = Instant::now();
+    loop {
+        if let Some(response) = get(address, path)
+            && f(&response)
+        {
+            return response;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(30),
+            "timed out waiting for {path}"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Serves the docs of a copy of the `standalone` fixture on a free port. Returns the temporary
+/// directory, the directory of the sources and the server.
+fn serve() -> Result<(tempfile::TempDir, PathBuf, DocServer)> {
+    let tmp_dir = tempfile::tempdir()?;
+    let sources = tmp_dir.path().join("sources");
+    copy_dir(Path::new("fixtures/standalone"), &sources)?;
+    let output = tmp_dir.path().join("output");
+
+    let server = Args::parse_from([
+        "wesldoc",
+        "serve",
+        sources.join("app").to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+        "--address",
+        "127.0.0.1:0",
+    ])
+    .start_server()?;
+
+    Ok((tmp_dir, sources, server))
+}
+
+#[test]
+fn serve_rebuilds_and_shows_errors() -> Result<()> {
+    let (_tmp_dir, sources, server) = serve()?;
+    let address = server.address().to_string();
+
+    // Pages are served with the live reload script
+    let page = "/app/latest/app/scene/fn.origin.html";
+    let response = wait_for(&address, page, |response| response.contains(" 200 OK"));
+    assert!(response.contains("/-/livereload"));
+    assert!(!response.contains("wesldoc-serve-error"));
+    let response = get(&address, "/../sources/app/wesl.toml").unwrap();
+    assert!(response.contains(" 404 "));
+    let response = get(&address, "/%2E%2E/sources/app/wesl.toml").unwrap();
+    assert!(response.contains(" 404 "));
+    let response = get(&address, "/app/latest/app/scene/fn%2Eorigin.html").unwrap();
+    assert!(response.contains(" 200 OK"));
+
+    // A syntax error is shown as an overlay instead of stopping the server
+    let scene = sources.join("app/shaders/scene.wesl");
+    let source = fs::read_to_string(&scene)?;
+    fs::write(&scene, source.replace("fn origin()", "fn origin("))?;
+    let response = wait_for(&address, page, |response| {
+        response.contains("wesldoc-serve-error")
+    });
+    assert!(response.contains("Build failed"));
+
+    // Fixing it rebuilds the docs
+    fs::write(
+        &scene,
+        source.replace("Returns the point at the origin.", "Returns the origin."),
+    )?;
+    let response = wait_for(&address, page, |response| {
+        !response.contains("wesldoc-serve-error") && response.contains("Returns the origin.")
+    });
+    assert!(response.contains(" 200 OK"));
+
+    server.shutdown()
+}
+
+#[test]
+fn serve_rebuilds_dependents() -> Result<()> {
+    let (_tmp_dir, sources, server) = serve()?;
+    let address = server.address().to_string();
+    let page = "/app/latest/app/scene/fn.origin.html";
+    wait_for(&address, page, |response| response.contains(" 200 OK"));
+
+    // Removing an item of the dependency breaks the package that imports it
+    let point = sources.join("geometry/shaders/point.wesl");
+    let source = fs::read_to_string(&point)?;
+    fs::write(&point, source.replace("struct Point", "struct Vector"))?;
+    wait_for(&address, page, |response| {
+        response.contains("wesldoc-serve-error")
+    });
+
+    server.shutdown()
+}
+
+#[test]
+fn serve_sends_reload_events() -> Result<()> {
+    let (_tmp_dir, sources, server) = serve()?;
+    let address = server.address().to_string();
+    wait_for(&address, "/app/latest/app/index.html", |response| {
+        response.contains(" 200 OK")
+    });
+
+    let mut stream = TcpStream::connect(&address)?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    write!(
+        stream,
+        "GET /-/livereload HTTP/1.1\r\nHost: {address}\r\n\r\n"
+    )?;
+    let mut events = BufReader::new(stream);
+    let mut line = String::new();
+    events.read_line(&mut line)?;
+    assert!(line.contains(" 200 OK"));
+    while line != "\r\n" {
+        line.clear();
+        events.read_line(&mut line)?;
+        assert!(!line.is_empty(), "event stream closed");
+    }
+
+    // Every finished build reloads the pages
+    let scene = sources.join("app/shaders/scene.wesl");
+    let source = fs::read_to_string(&scene)?;
+    fs::write(&scene, source.replace("Scene setup.", "The scene."))?;
+    loop {
+        line.clear();
+        events.read_line(&mut line)?;
+        assert!(!line.is_empty(), "event stream closed");
+        if line == "data: reload\n" {
+            break;
+        }
+    }
+
+    // Shutting down ends the event stream instead of leaving it open
+    server.shutdown()?;
+    events.read_to_string(&mut String::new())?;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use std::fs;
+use wesldoc::{Args, Parser};
+
+#[test]
+fn since_badges() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    for version in ["old", "new"] {
+        Args::parse_from([
+            "wesldoc",
+            "doc",
+            &format!("fixtures/diff/{version}"),
+            "--output",
+            tmp_dir.path().to_str().unwrap(),
+        ])
+        .run()?;
+    }
+    let lighting = tmp_dir.path().join("lights/1.1.0/lights/lighting");
+
+    // Items of the oldest documented version don't get a badge
+    let overview = fs::read_to_string(lighting.join("index.html"))?;
+    assert!(overview.contains(r#"falloff</a> <span class="since">since 1.1.0</span>"#));
+    assert!(!overview.contains(r#"shade</a> <span class="since">"#));
+
+    let falloff = fs::read_to_string(lighting.join("fn.falloff.html"))?;
+    assert!(falloff.contains("since 1.1.0"));
+    let shade = fs::read_to_string(lighting.join("fn.shade.html"))?;
+    assert!(shade.contains("changed in 1.1.0"));
+    assert!(!shade.contains(r#"class="since">since"#));
+
+    // An explicit tag overrides the computed version and is not rendered as text
+    let light = fs::read_to_string(lighting.join("struct.Light.html"))?;
+    assert!(light.contains("since 0.9.0"));
+    assert!(!light.contains("@since"));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_since_tag() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let package = tmp_dir.path().join("package");
+    fs::create_dir_all(package.join("shaders"))?;
+    let wesl_toml = "[package]\nname = \"tags\"\nversion = \"1.0.0\"\nedition = \"unstable_2025\"\nroot = \"shaders\"\n";
+    fs::write(package.join("wesl.toml"), wesl_toml)?;
+    fs::write(
+        package.join("shaders/tags.wesl"),
+        "/// A tagged function.\n///\n/// @since <img/src/onerror=alert(1)>\nfn tagged() {}\n",
+    )?;
+    let run = |output: &str| {
+        Args::parse_from([
+            "wesldoc",
+            "doc",
+            package.to_str().unwrap(),
+            "--output",
+            tmp_dir.path().join(output).to_str().unwrap(),
+        ])
+        .run()
+    };
+
+    // The tag is dropped instead of being rendered
+    run("output")?;
+    let tagged = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("output/tags/latest/tags/tags/fn.tagged.html"),
+    )?;
+    assert!(!tagged.contains("<img"));
+    assert!(!tagged.contains(r#"class="since""#));
+
+    // And reported
+    fs::write(
+        package.join("wesl.toml"),
+        format!("{wesl_toml}\n[wesldoc.lints]\ninvalid_doc_tags = \"deny\"\n"),
+    )?;
+    assert!(run("denied").is_err());
+
+    Ok(())
+}
@@ -0,0 +1 @@
+window.DOCS_THIS_VERSION = "0.0.0";
\ No newline at end of file
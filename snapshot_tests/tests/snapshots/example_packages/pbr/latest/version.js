@@ -0,0 +1 @@
+window.DOCS_THIS_VERSION = "latest";
\ No newline at end of file
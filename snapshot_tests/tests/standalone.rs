@@ -0,0 +1,96 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+fn run(path: &str, output: &Path) -> Result<()> {
+    Args::parse_from(["wesldoc", "doc", path, "--output", output.to_str().unwrap()]).run()
+}
+
+#[test]
+fn standalone_package_with_path_dependency() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run("fixtures/standalone/app", tmp_dir.path())?;
+
+    // Name and version come from wesl.toml
+    assert!(tmp_dir.path().join("app/0.2.0").is_dir());
+    assert!(tmp_dir.path().join("geometry/1.0.0").is_dir());
+
+    // The dependency is imported under its local name and linked by its package name
+    let origin = fs::read_to_string(tmp_dir.path().join("app/latest/app/scene/fn.origin.html"))?;
+    assert!(origin.contains("geometry/1.0.0/geometry/point/struct.Point.html"));
+
+    Ok(())
+}
+
+#[test]
+fn standalone_unversioned_path_dependency() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run("fixtures/standalone/sketch", tmp_dir.path())?;
+
+    // Only the dependency may omit its version, it gets a content-hash pseudo version
+    let thin = fs::read_to_string(
+        tmp_dir
+            .path()
+            .join("sketch/latest/sketch/canvas/fn.thin.html"),
+    )?;
+    assert!(thin.contains("brushes/0.0.0-local-"));
+    let versions = fs::read_dir(tmp_dir.path().join("brushes"))?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>>>()?;
+    assert!(
+        versions
+            .iter()
+            .any(|version| version.starts_with("0.0.0-local-"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn standalone_package_requires_version() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let err = run("fixtures/standalone/missing_version", tmp_dir.path()).unwrap_err();
+    assert!(err.to_string().contains("missing package version"));
+}
+
+#[test]
+fn standalone_package_rejects_package_selection() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    assert!(
+        Args::parse_from([
+            "wesldoc",
+            "doc",
+            "fixtures/standalone/app",
+            "--workspace",
+            "--output",
+            tmp_dir.path().to_str().unwrap(),
+        ])
+        .run()
+        .is_err()
+    );
+}
+
+#[test]
+fn timings_are_reported() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    Args::parse_from([
+        "wesldoc",
+        "doc",
+        "fixtures/standalone/app",
+        "--timings",
+        "--output",
+        tmp_dir.path().to_str().unwrap(),
+    ])
+    .run()?;
+
+    // Packages compiled concurrently are still generated in full
+    assert!(tmp_dir.path().join("app/0.2.0/app/index.html").is_file());
+    assert!(
+        tmp_dir
+            .path()
+            .join("geometry/1.0.0/geometry/index.html")
+            .is_file()
+    );
+
+    Ok(())
+}
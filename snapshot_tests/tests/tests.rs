@@ -22,10 +22,17 @@ fn snapshot_test(name: &str, packages: &[&str]) -> Result<()> {
         build_package(package, &tmp_dir)?;
     }
 
-    Assert::new()
+    let mut assert = Assert::new()
         .with_action_env_var("SNAPSHOTS")
-        .ignore("-/static")
-        .eq(format!("tests/snapshots/{name}"), tmp_dir.path());
+        .ignore("-/static");
+    // The API exports are covered by the diff tests, the build cache by the cache tests
+    for package in ["pbr", "math_utils", "primitives"] {
+        for version in ["latest", "0.0.0"] {
+            assert = assert.ignore(format!("{package}/{version}/api.json"));
+            assert = assert.ignore(format!("{package}/{version}/.wesldoc-cache.json"));
+        }
+    }
+    assert.eq(format!("tests/snapshots/{name}"), tmp_dir.path());
 
     Ok(())
 }
@@ -35,6 +42,7 @@ fn build_package(package_path: &str, tmp_dir: &tempfile::TempDir) -> Result<()>
 
     Args::parse_from([
         "wesldoc",
+        "doc",
         package_path,
         "--output",
         output_path.to_str().unwrap(),
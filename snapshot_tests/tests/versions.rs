@@ -0,0 +1,129 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser, exit_code};
+
+fn wesldoc(args: &[&str], output: &Path) -> Result<()> {
+    let mut args = [&["wesldoc"], args].concat();
+    args.extend(["--output", output.to_str().unwrap()]);
+    Args::parse_from(args).run()
+}
+
+fn document(versions: &[&str], output: &Path) -> Result<()> {
+    for version in versions {
+        wesldoc(&["doc", &format!("fixtures/diff/{version}")], output)?;
+    }
+    Ok(())
+}
+
+fn latest_version(lights: &Path) -> Result<String> {
+    let api = fs::read_to_string(lights.join("latest/api.json"))?;
+    let version = api
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(r#""version": ""#))
+        .unwrap();
+    Ok(version.trim_end_matches(['"', ',']).to_string())
+}
+
+#[test]
+fn latest_skips_prereleases() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    document(&["old", "new", "beta"], tmp_dir.path())?;
+    let lights = tmp_dir.path().join("lights");
+
+    assert_eq!(latest_version(&lights)?, "1.1.0");
+    assert!(lights.join("2.0.0-beta.1/lights/index.html").is_file());
+
+    Ok(())
+}
+
+#[test]
+fn yank_retargets_latest() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    document(&["old", "new"], tmp_dir.path())?;
+    let lights = tmp_dir.path().join("lights");
+
+    wesldoc(&["versions", "yank", "lights", "1.1.0"], tmp_dir.path())?;
+    assert_eq!(latest_version(&lights)?, "1.0.0");
+    let common = fs::read_to_string(lights.join("common.js"))?;
+    assert!(common.contains(r#""yanked": ["#));
+    // Pages are copied as they are, only the version they are shown as is rendered for latest
+    assert_eq!(
+        fs::read(lights.join("latest/lights/index.html"))?,
+        fs::read(lights.join("1.0.0/lights/index.html"))?
+    );
+    let version = fs::read_to_string(lights.join("latest/version.js"))?;
+    assert!(version.contains(r#""latest""#));
+    assert!(lights.join("1.1.0/lights/index.html").is_file());
+
+    wesldoc(
+        &["versions", "yank", "lights", "1.1.0", "--undo"],
+        tmp_dir.path(),
+    )?;
+    assert_eq!(latest_version(&lights)?, "1.1.0");
+    let common = fs::read_to_string(lights.join("common.js"))?;
+    assert!(!common.contains("yanked"));
+
+    Ok(())
+}
+
+#[test]
+fn remove_versions() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    document(&["old", "new", "beta"], tmp_dir.path())?;
+    let lights = tmp_dir.path().join("lights");
+
+    wesldoc(&["versions", "remove", "lights", "1.1.0"], tmp_dir.path())?;
+    assert!(!lights.join("1.1.0").exists());
+    assert_eq!(latest_version(&lights)?, "1.0.0");
+    let common = fs::read_to_string(lights.join("common.js"))?;
+    assert!(!common.contains("1.1.0"));
+
+    // Pre-releases are only used when there is no stable version left
+    wesldoc(&["versions", "remove", "lights", "1.0.0"], tmp_dir.path())?;
+    assert_eq!(latest_version(&lights)?, "2.0.0-beta.1");
+
+    wesldoc(
+        &["versions", "remove", "lights", "2.0.0-beta.1"],
+        tmp_dir.path(),
+    )?;
+    assert!(!lights.exists());
+    let index = fs::read_to_string(tmp_dir.path().join("index.html"))?;
+    assert!(!index.contains("lights"));
+
+    Ok(())
+}
+
+#[test]
+fn aliases() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    document(&["new", "beta"], tmp_dir.path())?;
+    let lights = tmp_dir.path().join("lights");
+
+    wesldoc(
+        &["versions", "alias", "lights", "next", "2.0.0-beta.1"],
+        tmp_dir.path(),
+    )?;
+    let common = fs::read_to_string(lights.join("common.js"))?;
+    assert!(common.contains(r#""next": "2.0.0-beta.1""#));
+
+    wesldoc(&["versions", "alias", "lights", "next"], tmp_dir.path())?;
+    let common = fs::read_to_string(lights.join("common.js"))?;
+    assert!(!common.contains("aliases"));
+
+    // Aliases must not shadow versions
+    let err = wesldoc(
+        &["versions", "alias", "lights", "latest", "1.1.0"],
+        tmp_dir.path(),
+    )
+    .unwrap_err();
+    assert_eq!(exit_code(&err), 2);
+    assert!(
+        wesldoc(
+            &["versions", "alias", "lights", "next", "3.0.0"],
+            tmp_dir.path()
+        )
+        .is_err()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+use wesldoc::{Args, Parser};
+
+fn run(output: &Path, extra_args: &[&str]) -> Result<()> {
+    Args::parse_from(
+        [
+            "wesldoc",
+            "doc",
+            "fixtures/workspace",
+            "--output",
+            output.to_str().unwrap(),
+        ]
+        .into_iter()
+        .chain(extra_args.iter().copied()),
+    )
+    .run()
+}
+
+#[test]
+fn virtual_workspace_documents_all_members() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(tmp_dir.path(), &[])?;
+
+    for member in ["shapes", "render", "extra"] {
+        assert!(tmp_dir.path().join(member).join("latest").is_dir());
+    }
+
+    // Members are listed on the top-level index
+    let index = fs::read_to_string(tmp_dir.path().join("index.html"))?;
+    assert!(index.contains("Workspace members"));
+    assert!(index.contains("./render/latest/render/index.html"));
+
+    Ok(())
+}
+
+#[test]
+fn workspace_exclude() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(tmp_dir.path(), &["--workspace", "--exclude", "extra"])?;
+
+    assert!(tmp_dir.path().join("render").is_dir());
+    assert!(tmp_dir.path().join("shapes").is_dir());
+    assert!(!tmp_dir.path().join("extra").exists());
+
+    Ok(())
+}
+
+#[test]
+fn select_package() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    run(tmp_dir.path(), &["-p", "render"])?;
+
+    // Dependencies of the selected package are still documented
+    assert!(tmp_dir.path().join("render").is_dir());
+    assert!(tmp_dir.path().join("shapes").is_dir());
+    assert!(!tmp_dir.path().join("extra").exists());
+
+    Ok(())
+}
+
+#[test]
+fn unknown_package_fails() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    assert!(run(tmp_dir.path(), &["-p", "does_not_exist"]).is_err());
+    assert!(run(tmp_dir.path(), &["--exclude", "does_not_exist"]).is_err());
+}